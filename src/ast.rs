@@ -1,31 +1,204 @@
 #![allow(dead_code)]
 use core::slice::Iter;
-use std::{iter::Peekable, collections::VecDeque};
+use std::{fmt::Display, iter::Peekable};
 
-use crate::{token::Token, errors::{Error, CompilerResult, Location, PartialLocation, self}};
+use crate::{token::{Keyword, Position, Span, SpannedToken, Token, MAX_INT_MAGNITUDE}, errors::{Error, CompilerResult, Location, PartialLocation}};
+
+
+/// the type of a value
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Type{
+    Int,
+    Float,
+    String,
+    Bool
+}
+
+impl Display for Type{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::String => write!(f, "string"),
+            Type::Bool => write!(f, "bool"),
+        }
+    }
+}
+
+impl Type{
+    /// the type written in an annotation, like `int`
+    pub fn from_name(name: &str) -> Option<Self>{
+        match name {
+            "int" => Some(Type::Int),
+            "float" => Some(Type::Float),
+            "string" => Some(Type::String),
+            "bool" => Some(Type::Bool),
+            _ => None
+        }
+    }
+}
+
+/// a parameter of a function, `name` or `name: type`
+#[derive(Clone, PartialEq, PartialOrd, Debug)]
+pub struct Param{
+    pub identifier: String,
+    pub ty: Option<Type>, // None if it is not annotated, then the function takes any type
+    pub location: Location // the location of the name
+}
 
 
 /// all the operator.
-/// Mul and Div won't be implement due to their complexity.
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum Operator{
     Plus,
     Minus,
     Mul,
-    Div
+    Div,
+    And,
+    Or,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual
 }
 
+impl Operator{
+    /// convert a token to an operator
+    /// returns None if the token is not an operator
+    pub fn from_token(token: &Token) -> Option<Self>{
+        match token {
+            Token::Plus => Some(Operator::Plus),
+            Token::Minus => Some(Operator::Minus),
+            Token::Mul => Some(Operator::Mul),
+            Token::Div => Some(Operator::Div),
+            Token::Equal => Some(Operator::Equal),
+            Token::NotEqual => Some(Operator::NotEqual),
+            Token::Less => Some(Operator::Less),
+            Token::LessEqual => Some(Operator::LessEqual),
+            Token::Greater => Some(Operator::Greater),
+            Token::GreaterEqual => Some(Operator::GreaterEqual),
+            Token::Keyword(Keyword::And) => Some(Operator::And),
+            Token::Keyword(Keyword::Or) => Some(Operator::Or),
+            _ => None
+        }
+    }
 
-/// A Node is value. A Node can be composed of a lot of other Node.
+    /// the precedence of the operator, an operator with a higher precedence is evaluated first
+    /// like in C: * and / bind tighter than + and -, which bind tighter than <, <=, > and >=, then come == and !=, then and, and then or
+    /// so `a + 1 < b == c` is `((a + 1) < b) == c` and `a or b and c` is `a or (b and c)`
+    pub const fn precedence(&self) -> u8{
+        match self {
+            Operator::Or => 1,
+            Operator::And => 2,
+            Operator::Equal | Operator::NotEqual => 3,
+            Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual => 4,
+            Operator::Plus | Operator::Minus => 5,
+            Operator::Mul | Operator::Div => 6
+        }
+    }
+
+    /// check if the operator compares its operands instead of computing a number
+    pub fn is_comparison(&self) -> bool{
+        matches!(self, Operator::Equal | Operator::NotEqual | Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual)
+    }
+
+    /// check if the operator is `and` or `or`, which only evaluate their right operand if it is needed
+    pub fn is_logical(&self) -> bool{
+        matches!(self, Operator::And | Operator::Or)
+    }
+
+    /// compare two values with the operator
+    /// returns None if the operator is not a comparison
+    pub fn compare<T: PartialOrd + ?Sized>(&self, l: &T, r: &T) -> Option<bool>{
+        match self {
+            Operator::Equal => Some(l == r),
+            Operator::NotEqual => Some(l != r),
+            Operator::Less => Some(l < r),
+            Operator::LessEqual => Some(l <= r),
+            Operator::Greater => Some(l > r),
+            Operator::GreaterEqual => Some(l >= r),
+            Operator::Plus | Operator::Minus | Operator::Mul | Operator::Div | Operator::And | Operator::Or => None
+        }
+    }
+}
+
+impl Display for Operator{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operator::Plus => write!(f, "+"),
+            Operator::Minus => write!(f, "-"),
+            Operator::Mul => write!(f, "*"),
+            Operator::Div => write!(f, "/"),
+            Operator::And => write!(f, "and"),
+            Operator::Or => write!(f, "or"),
+            Operator::Equal => write!(f, "=="),
+            Operator::NotEqual => write!(f, "!="),
+            Operator::Less => write!(f, "<"),
+            Operator::LessEqual => write!(f, "<="),
+            Operator::Greater => write!(f, ">"),
+            Operator::GreaterEqual => write!(f, ">="),
+        }
+    }
+}
+
+
+/// `not` takes the comparisons: `not a == b` is `not (a == b)`, but `not a and b` is `(not a) and b`
+/// so its operand is made of the operators which bind at least as tight as ==
+const NOT_PRECEDENCE: u8 = Operator::Equal.precedence();
+
+
+/// the operators with a single operand, before it
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum UnaryOperator{
+    /// -a
+    Neg,
+    /// not a
+    Not
+}
+
+impl Display for UnaryOperator{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnaryOperator::Neg => write!(f, "-"),
+            UnaryOperator::Not => write!(f, "not"),
+        }
+    }
+}
+
+
+/// A Node is value. A Node can be composed of a lot of other Node.
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, PartialEq, PartialOrd, Debug)]
 pub enum Expr{
-    IntLitteral(u64),
+    IntLitteral(i64),
+    FloatLitteral(f64),
     StringLitteral(String),
-    Identifier(String),
+    BoolLitteral(bool),
+    /// `x`, the location is the one of the name
+    Identifier{
+        identifier: String,
+        location: Location
+    },
+    /// `f(a, b)`, the location is the one of the name of the function
+    Call{
+        identifier: String,
+        args: Vec<Expr>,
+        location: Location
+    },
+    /// `a + b`, the location is the one of the operator
     BinaryExpr{
         opr: Operator,
         l: Box<Expr>,
-        r: Box<Expr>
+        r: Box<Expr>,
+        location: Location
+    },
+    /// `-a` or `not a`, the location is the one of the operator
+    UnaryExpr{
+        opr: UnaryOperator,
+        value: Box<Expr>,
+        location: Location
     },
     /// A block is a suite of instruction.
     Block{
@@ -40,221 +213,780 @@ pub enum Expr{
 /// var x = (25+25)
 /// ```
 /// This previous line can't be evalued, but it is composed of multiple node.
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+/// Each statement knows where it begins, so the errors found after the parsing can be located.
+#[derive(Clone, PartialEq, PartialOrd, Debug)]
 pub enum Statement{
+    /// `var x = 1` or `var x: int = 1`
     VarDeclaration{
         identifier: String,
-        value: Expr
+        ty: Option<Type>,
+        value: Expr,
+        location: Location
     },
     Print{
-        value: Expr
+        value: Expr,
+        location: Location
     },
     VarEdit{
         identifier: String,
-        value: Expr
+        value: Expr,
+        location: Location
     },
+    /// a function call alone on its line, its value is not used
     FuncCall{
         identifier: String,
         args: Vec<Expr>,
+        location: Location
+    },
+    /// `fn name(a, b: int) -> int { ... }`, the body is a Expr::Block
+    /// `returns` is None if the type returned is not annotated
+    FuncDeclaration{
+        identifier: String,
+        params: Vec<Param>,
+        returns: Option<Type>,
+        body: Expr,
+        location: Location
+    },
+    /// leave the function, with a value or not
+    Return{
+        value: Option<Expr>,
+        location: Location
+    },
+    /// an expression alone on its line, the REPL prints its value
+    Expr{
+        value: Expr,
+        location: Location
+    },
+    /// `if condition { ... } else { ... }`, the bodies are Expr::Block
+    /// `else if` is an else body with a single If statement
+    If{
+        condition: Expr,
+        body: Expr,
+        else_body: Option<Expr>,
+        location: Location
+    },
+    /// `while condition { ... }`, the body is a Expr::Block
+    While{
+        condition: Expr,
+        body: Expr,
+        location: Location
+    },
+    /// leave the innermost loop
+    Break{
+        location: Location
+    },
+    /// go to the next iteration of the innermost loop
+    Continue{
+        location: Location
     },
     NoneOrError // indicate either the statement is none or an error
 }
 
+impl Statement{
+    /// where the statement begins
+    pub fn location(&self) -> Option<&Location>{
+        match self {
+            ST::VarDeclaration { location, .. } | ST::Print { location, .. } | ST::VarEdit { location, .. } | ST::FuncCall { location, .. }
+            | ST::Expr { location, .. } | ST::If { location, .. } | ST::While { location, .. } | ST::Break { location }
+            | ST::Continue { location } | ST::FuncDeclaration { location, .. } | ST::Return { location, .. } => Some(location),
+            ST::NoneOrError => None
+        }
+    }
+}
+
 use Statement as ST;
 
+impl Expr{
+    /// the location of a variable, a call or an operator, None for a litteral or a block
+    pub fn location(&self) -> Option<&Location>{
+        match self {
+            Expr::Identifier { location, .. } | Expr::Call { location, .. } | Expr::BinaryExpr { location, .. } | Expr::UnaryExpr { location, .. } => Some(location),
+            Expr::IntLitteral(_) | Expr::FloatLitteral(_) | Expr::StringLitteral(_) | Expr::BoolLitteral(_) | Expr::Block { .. } | Expr::Error => None
+        }
+    }
+}
+
 /// contruct an Abstract Syntax Tree (AST) from a list of vectors
+/// There is one statement per line, except the statements with blocks which can be on several lines.
 pub struct AbstractSyntaxTree<'a>{
-    statement: Statement,
-    tokens: Peekable<Iter<'a, Token>>,
+    statements: Vec<Statement>,
+    tokens: Peekable<Iter<'a, SpannedToken>>,
     err: Vec<Error>,
     curr: Option<&'a Token>,
+    span: Span, // where the current token is, after the last token if there are no more
     pl: PartialLocation,
-    line: String
+    lines: Vec<String>, // the lines of the source code, used by errors
+    loops: u32, // the number of loops around the current statement, in the current function
+    blocks: u32, // the number of blocks around the current statement
+    in_function: bool
 }
 
 
 impl<'a> AbstractSyntaxTree<'a>{
 
-    pub fn new(tokens: &'a Vec<Token>, pl: PartialLocation, line: &str) -> Self{
-        Self { statement: ST::NoneOrError, tokens: tokens.iter().peekable(), err: Vec::new(), curr: None, pl, line: line.into()}
+    /// `source` is the code which was tokenized and `pl` the location of its first line
+    pub fn new(tokens: &'a [SpannedToken], pl: PartialLocation, source: &str) -> Self{
+        let lines = source.lines().map(String::from).collect();
+        let span = Span::at(Position { line: pl.get_line(), ..Default::default() });
+        Self { statements: Vec::new(), tokens: tokens.iter().peekable(), err: Vec::new(), curr: None, span, pl, lines, loops: 0, blocks: 0, in_function: false}
     }
 
     fn advance(&mut self){
-        self.curr = self.tokens.next();
+        match self.tokens.next() {
+            Some(tk) => {
+                self.curr = Some(&tk.token);
+                self.span = tk.span;
+            },
+            None => {
+                self.curr = None;
+                self.span = Span::at(self.span.end);
+            }
+        }
+    }
+
+    /// the token after the current one
+    fn peek(&mut self) -> Option<&'a Token>{
+        self.tokens.peek().map(|tk| &tk.token)
     }
+
+    /// the location of the current token
+    fn location(&self) -> Location{
+        Location::from(self.pl.clone()).line(self.span.start.line).char_pos(self.span.start.column)
+    }
+
+    /// the line of code of the current token
+    /// a token can be several lines after the previous one, after a comment for example
+    fn line(&self) -> String{
+        let index = self.span.start.line.saturating_sub(self.pl.get_line()) as usize;
+        self.lines.get(index).cloned().unwrap_or_default()
+    }
+
+    /// push an excepted token error
+    fn excepted(&mut self, excepted: &str){
+        self.err.push(Error::excepted_token(
+            self.location(),
+            self.line(), excepted.into())
+        );
+    }
+
+    /// push an unexcepted token error
+    fn unexcepted(&mut self, token: &Token){
+        let token = match token {
+            Token::Keyword(keyword) => format!("keyword {keyword}"),
+            token => format!("{:?}", token)
+        };
+        self.err.push(Error::unexcepted_token(
+            self.location(),
+            self.line(), token)
+        );
+    }
+
+    /// skip tokens after an error until a token that can follow a value is found: an operator, a closing paren, a coma, a brace or the end of the line.
+    /// That way the parser can continue and report the other mistakes of the line.
+    fn synchronize(&mut self){
+        while let Some(tk) = self.curr{
+            if Operator::from_token(tk).is_some() || matches!(tk, Token::ClosingParen | Token::Coma | Token::NewLine | Token::OpeningBrace | Token::ClosingBrace){
+                break;
+            }
+            self.advance();
+        }
+    }
+
     /// make a node from a list of token
-    /// This might call itself recursively
+    /// It uses precedence climbing, so `1 + 2 * 3` is parsed as `1 + (2 * 3)`
+    /// and operators of the same precedence are left-associative: `1 - 2 - 3` is `(1 - 2) - 3`
     fn make_expr(&mut self) -> Expr{
+        self.make_binary_expr(1)
+    }
+
+    /// make a binary expression where every operator has at least a precedence of `min_precedence`
+    /// This might call itself recursively
+    fn make_binary_expr(&mut self, min_precedence: u8) -> Expr{
+        let mut left = self.make_operand();
+
+        while let Some(tk) = self.curr{
+            let opr = match Operator::from_token(tk) {
+                Some(opr) => opr,
+                None => {
+                    match tk {
+                        // the end of a sub-expression, of an argument, of a condition or of the statement
+                        Token::ClosingParen | Token::Coma | Token::NewLine | Token::OpeningBrace | Token::ClosingBrace => break,
+                        _ => {
+                            self.excepted("Operator");
+                            self.synchronize();
+                            left = Expr::Error;
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            if opr.precedence() < min_precedence{
+                // the operator belongs to a caller with a lower precedence
+                break;
+            }
+
+            let location = self.location();
+            self.advance(); // skip the operator
+
+            // the right side only takes operators which bind tighter, that's what makes it left-associative
+            let right = self.make_binary_expr(opr.precedence() + 1);
+            left = Expr::BinaryExpr { opr, l: Box::new(left), r: Box::new(right), location };
+        }
 
+        left
+    }
+
+    /// make a single value: a litteral, a variable, a call, a parenthesised expression, a negation or a not
+    /// returns Expr::Error and push an error if there is no value
+    fn make_operand(&mut self) -> Expr{
         match self.curr {
             Some(tk) => {
                 match tk {
                     Token::Int(val) => {
-
+                        // the magnitude of i64::MIN is only valid after a minus
+                        let Ok(val) = i64::try_from(*val) else {
+                            self.err.push(Error::integer_overflow(self.location(), self.line()));
+                            self.advance();
+                            return Expr::Error;
+                        };
                         self.advance();
-                        match self.curr{ // check next token
-
-                            Some(possible_operator) => {
-                                // we check if it's an operator or a closing paren
-                                println!("a token was found");
-
-                                match possible_operator {
-                                    Token::Plus => {
-                                        self.advance();
-                                        Expr::BinaryExpr { l: Box::new(Expr::IntLitteral(*val)), r: Box::new(self.make_expr()), opr: Operator::Plus }
-                                    },
-
-                                    Token::ClosingParen => {// if it's a paren just return the value
-                                        Expr::IntLitteral(*val)
-                                    }
-                                    Token::Minus | Token::Div | Token::Mul => {
-                                        todo!("implement other operator in make_expr")
-                                    }
+                        Expr::IntLitteral(val)
+                    },
 
-                                    _ => {
-                                        panic!("Excepted operator, found {:?}", possible_operator);
-                                    }
+                    Token::Float(val) => {
+                        self.advance();
+                        Expr::FloatLitteral(*val)
+                    },
 
-                                }
-                            },
-                            None => { // no more token
-                                
-                                Expr::IntLitteral(*val)
-                            }
+                    Token::Minus => {
+                        // the negation binds tighter than every binary operator: -a * b is (-a) * b
+                        let location = self.location();
+                        self.advance();
+                        if let Some(Token::Int(MAX_INT_MAGNITUDE)) = self.curr{
+                            // -9223372036854775808 is the smallest int, its magnitude is not an int
+                            self.advance();
+                            return Expr::IntLitteral(i64::MIN);
                         }
+                        Expr::UnaryExpr { opr: UnaryOperator::Neg, value: Box::new(self.make_operand()), location }
                     },
 
                     Token::String(val) => {
                         self.advance();
-                        match self.curr{ // check next token
-
-                            Some(possible_operator) => {
-                                // we check if it's an operator or a closing paren
-                                println!("a token was found");
+                        Expr::StringLitteral(val.clone())
+                    },
 
-                                match possible_operator {
-                                    Token::Plus => {
-                                        self.advance();
-                                        Expr::BinaryExpr { l: Box::new(Expr::StringLitteral(val.clone())), r: Box::new(self.make_expr()), opr: Operator::Plus }
-                                    },
+                    Token::Keyword(keyword @ (Keyword::True | Keyword::False)) => {
+                        self.advance();
+                        Expr::BoolLitteral(keyword == &Keyword::True)
+                    },
 
-                                    Token::ClosingParen => {// if it's a paren just return the value
-                                        Expr::StringLitteral(val.clone())
-                                    }
-                                    Token::Minus | Token::Div | Token::Mul => {
-                                        todo!("implement other operator in make_expr")
-                                    }
+                    Token::Keyword(Keyword::Not) => {
+                        let location = self.location();
+                        self.advance();
+                        Expr::UnaryExpr { opr: UnaryOperator::Not, value: Box::new(self.make_binary_expr(NOT_PRECEDENCE)), location }
+                    },
 
-                                    _ => {
-                                        panic!("Excepted operator, found {:?}", possible_operator);
-                                    }
+                    Token::Identifier(name) => {
+                        let location = self.location();
+                        self.advance();
 
-                                }
-                            },
-                            None => { // no more token
-                                
-                                Expr::StringLitteral(val.clone())
-                            }
+                        if let Some(Token::OpeningParen) = self.curr{
+                            Expr::Call { identifier: name.clone(), args: self.make_args(), location }
+                        }
+                        else {
+                            Expr::Identifier { identifier: name.clone(), location }
                         }
                     },
 
                     Token::OpeningParen => {
                         self.advance(); // skip the opening paren
-                        if let Some(token) = self.curr{
-                            if let Token::ClosingParen = token{
-                                panic!("unit-type are not allowed")
-
-                            }
+                        if let Some(Token::ClosingParen) = self.curr{
+                            // unit-type are not allowed
+                            self.excepted("Expression");
+                            self.advance();
+                            return Expr::Error;
                         }
 
-
                         let tmp = self.make_expr();
                         match self.curr{
+                            Some(Token::ClosingParen) => {
+                                self.advance();
+                                tmp
+                            },
+                            Some(Token::NewLine) | None => {
+                                self.excepted("ClosingParen");
+                                Expr::Error
+                            },
                             Some(tk) => {
-                                match tk {
-                                    Token::ClosingParen => {
-                                        self.advance();
-                                        tmp
-                                    },
-                                    _ => {
-                                        panic!("a token was found but not an closing paren");
+                                self.unexcepted(tk);
+                                // skip the rest of the parenthesis
+                                while let Some(tk) = self.curr{
+                                    if tk == &Token::NewLine{
+                                        break;
+                                    }
+                                    self.advance();
+                                    if tk == &Token::ClosingParen{
+                                        break;
                                     }
                                 }
-                            },
-                            None => {
-                                panic!("missing closing paren")
+                                Expr::Error
                             }
                         }
 
                     },
+                    Token::ClosingParen => {
+                        // don't skip it, it closes the enclosing parenthesis
+                        self.unexcepted(tk);
+                        Expr::Error
+                    },
+                    Token::NewLine => {
+                        self.excepted("Expression");
+                        Expr::Error
+                    },
                     _ => {
-                        panic!("unexcepted token {:?}", tk)
+                        self.unexcepted(tk);
+                        self.advance();
+                        self.synchronize();
+                        Expr::Error
                     }
                 }
             }
-            None => Expr::Error
+            None => {
+                self.excepted("Expression");
+                Expr::Error
+            }
         }
-
     }
 
-    fn make_var_statement(&mut self) -> Statement{
-        // first token must be Keyword("var")
-        // now we check that the second is an identifier
-        
-        //TODO redo
+    /// make a type annotation, the current token must be the colon or the arrow
+    /// returns None and push an error if it is not a type
+    fn make_type(&mut self) -> Option<Type>{
+        self.advance(); // skip the colon or the arrow
 
-        let mut identifier = String::new();
+        match self.curr {
+            Some(Token::Identifier(name)) => {
+                let ty = Type::from_name(name);
+                if ty.is_none(){
+                    self.err.push(Error::unknown_type(self.location(), self.line(), name.clone()));
+                }
+                self.advance();
+                ty
+            },
+            _ => {
+                self.excepted("Type");
+                None
+            }
+        }
+    }
 
-        self.advance();
+    /// make a var declaration: `var <identifier> = <expr>` or `var <identifier>: <type> = <expr>`
+    fn make_var_statement(&mut self, location: Location) -> Statement{
+        self.advance(); // skip the var keyword
 
-        match self.curr {
+        let identifier = match self.curr {
             Some(Token::Identifier(id)) => {
-                identifier = id.clone();
+                self.advance();
+                id.clone()
             },
             _ => {
-                self.err.push(Error::excepted_token(
-                    Location::from(self.pl.clone()), 
-                    self.line.clone(), "Identifier".into())
-                )
+                self.excepted("Identifier");
+                return ST::NoneOrError;
             }
-            
         };
 
-        self.advance();
+        let ty = match self.curr {
+            Some(Token::Colon) => match self.make_type() {
+                Some(ty) => Some(ty),
+                None => return ST::NoneOrError
+            },
+            _ => None
+        };
+
+        match self.curr {
+            Some(Token::Assign) => self.advance(),
+            _ => {
+                self.excepted("Assign");
+                return ST::NoneOrError;
+            }
+        };
+
+        Statement::VarDeclaration { identifier, ty, value: self.make_expr(), location }
+    }
+
+    /// make a print statement: `print <expr>`
+    fn make_print_statement(&mut self, location: Location) -> Statement{
+        self.advance(); // skip the print keyword
+
+        Statement::Print { value: self.make_expr(), location }
+    }
+
+    /// make a reassignment: `x = <expr>`
+    fn make_identifier_statement(&mut self, identifier: String, location: Location) -> Statement{
+        self.advance(); // skip the identifier
 
         match self.curr {
             Some(Token::Assign) => {
-                // nice
+                self.advance();
+                Statement::VarEdit { identifier, value: self.make_expr(), location }
             },
             _ => {
-                self.err.push(Error::excepted_token(
-                    Location::from(self.pl.clone()), 
-                    self.line.clone(), "Assign".into())
-                )
+                self.excepted("Assign");
+                ST::NoneOrError
+            }
+        }
+    }
+
+    /// make the arguments of a function call: `(<expr>, ...)`
+    /// the current token must be the opening paren
+    fn make_args(&mut self) -> Vec<Expr>{
+        let mut args = Vec::new();
+        self.advance(); // skip the opening paren
+
+        if let Some(Token::ClosingParen) = self.curr{ // no arguments
+            self.advance();
+            return args;
+        }
+
+        loop {
+            args.push(self.make_expr());
+
+            match self.curr {
+                Some(Token::Coma) => self.advance(),
+                Some(Token::ClosingParen) => {
+                    self.advance();
+                    break;
+                },
+                Some(Token::NewLine) | None => {
+                    self.excepted("ClosingParen");
+                    break;
+                },
+                Some(tk) => {
+                    self.unexcepted(tk);
+                    break;
+                }
+            }
+        }
+
+        args
+    }
+
+    /// skip the rest of a line after an error, with the blocks opened on it
+    /// stops at the end of the line or at a } which closes a block opened before the line
+    fn skip_line(&mut self){
+        let mut depth = 0;
+
+        while let Some(tk) = self.curr{
+            match tk {
+                Token::NewLine if depth == 0 => break,
+                Token::ClosingBrace if depth == 0 => break,
+                Token::ClosingBrace => depth -= 1,
+                Token::OpeningBrace => depth += 1,
+                _ => ()
+            }
+            self.advance();
+        }
+    }
+
+    /// make a block: statements between braces, one per line
+    /// The braces can be on the same line than the statements: `{ print x }`
+    /// returns Expr::Error if there is no {
+    fn make_block(&mut self) -> Expr{
+        if self.curr != Some(&Token::OpeningBrace){
+            self.excepted("{");
+            return Expr::Error;
+        }
+        self.advance();
+
+        self.blocks += 1;
+        let code = self.make_block_code();
+        self.blocks -= 1;
+
+        Expr::Block { code }
+    }
+
+    /// make the statements of a block, until its }
+    fn make_block_code(&mut self) -> Vec<Statement>{
+        let mut code = Vec::new();
+        loop {
+            match self.curr {
+                Some(Token::NewLine) => self.advance(),
+
+                Some(Token::ClosingBrace) => {
+                    self.advance();
+                    return code;
+                },
+
+                None => {
+                    self.excepted("}");
+                    return code;
+                },
+
+                Some(_) => {
+                    let err_count = self.err.len();
+                    let statement = self.make_statement();
+
+                    // the statement must end with its line or with the block
+                    if let Some(tk) = self.curr{
+                        if !matches!(tk, Token::NewLine | Token::ClosingBrace){
+                            if self.err.len() == err_count{
+                                self.unexcepted(tk);
+                            }
+                            self.skip_line();
+                        }
+                    }
+
+                    if statement != ST::NoneOrError{
+                        code.push(statement);
+                    }
+                }
+            }
+        }
+    }
+
+    /// make a if statement: `if condition { ... }`
+    /// it can be followed by `else { ... }` or by `else if`, on the line of the }
+    fn make_if_statement(&mut self, location: Location) -> Statement{
+        self.advance(); // skip the if
+        let err_count = self.err.len();
+        let condition = self.make_expr();
+
+        if self.err.len() != err_count && self.curr != Some(&Token::OpeningBrace){
+            // the condition was already reported, a missing { would be the same mistake
+            return ST::NoneOrError;
+        }
+        let body = self.make_block();
+
+        let mut else_body = None;
+        if self.curr == Some(&Token::Keyword(Keyword::Else)){
+            self.advance();
+
+            if self.curr == Some(&Token::Keyword(Keyword::If)){
+                let location = self.location();
+                else_body = Some(Expr::Block { code: vec![self.make_if_statement(location)] });
+            }
+            else {
+                else_body = Some(self.make_block());
+            }
+        }
+
+        ST::If { condition, body, else_body, location }
+    }
+
+    /// make a while statement: `while condition { ... }`
+    fn make_while_statement(&mut self, location: Location) -> Statement{
+        self.advance(); // skip the while
+        let err_count = self.err.len();
+        let condition = self.make_expr();
+
+        if self.err.len() != err_count && self.curr != Some(&Token::OpeningBrace){
+            // the condition was already reported, a missing { would be the same mistake
+            return ST::NoneOrError;
+        }
+
+        self.loops += 1;
+        let body = self.make_block();
+        self.loops -= 1;
+
+        ST::While { condition, body, location }
+    }
+
+    /// make a function declaration: `fn name(a, b) { ... }` or `fn name(a: int, b) -> int { ... }`
+    /// the functions can only be declared outside of the blocks
+    fn make_function_statement(&mut self, location: Location) -> Statement{
+        if self.blocks > 0{
+            self.err.push(Error::nested_function(location.clone(), self.line()));
+        }
+        self.advance(); // skip the fn
+
+        let identifier = match self.curr {
+            Some(Token::Identifier(identifier)) => identifier.clone(),
+            _ => {
+                self.excepted("Identifier");
+                return ST::NoneOrError;
             }
         };
+        self.advance();
+
+        let params = match self.make_params() {
+            Some(params) => params,
+            None => return ST::NoneOrError
+        };
+
+        let returns = match self.curr {
+            Some(Token::Arrow) => match self.make_type() {
+                Some(ty) => Some(ty),
+                None => return ST::NoneOrError
+            },
+            _ => None
+        };
 
+        // a loop around the function does not contain its body
+        let loops = std::mem::replace(&mut self.loops, 0);
+        let in_function = std::mem::replace(&mut self.in_function, true);
+        let body = self.make_block();
+        self.loops = loops;
+        self.in_function = in_function;
+
+        ST::FuncDeclaration { identifier, params, returns, body, location }
+    }
+
+    /// make the parameters of a function declaration: `(a, b: int)`
+    /// returns None and push an error if they are malformed
+    fn make_params(&mut self) -> Option<Vec<Param>>{
+        let mut params = Vec::new();
+        if self.curr != Some(&Token::OpeningParen){
+            self.excepted("OpeningParen");
+            return None;
+        }
         self.advance();
 
-        Statement::VarDeclaration { identifier: identifier, value: self.make_expr() }
+        if let Some(Token::ClosingParen) = self.curr{ // no parameters
+            self.advance();
+            return Some(params);
+        }
+
+        loop {
+            match self.curr {
+                Some(Token::Identifier(identifier)) => {
+                    let location = self.location();
+                    self.advance();
+                    let ty = match self.curr {
+                        Some(Token::Colon) => Some(self.make_type()?),
+                        _ => None
+                    };
+                    params.push(Param { identifier: identifier.clone(), ty, location });
+                },
+                _ => {
+                    self.excepted("Identifier");
+                    return None;
+                }
+            }
+
+            match self.curr {
+                Some(Token::Coma) => self.advance(),
+                Some(Token::ClosingParen) => {
+                    self.advance();
+                    return Some(params);
+                },
+                _ => {
+                    self.excepted("ClosingParen");
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// make a return statement: `return` or `return <expr>`, it must be in a function
+    fn make_return_statement(&mut self, location: Location) -> Statement{
+        if !self.in_function{
+            self.err.push(Error::outside_function(location.clone(), self.line()));
+        }
+        self.advance(); // skip the return
+
+        let value = match self.curr {
+            Some(Token::NewLine | Token::ClosingBrace) | None => None,
+            Some(_) => Some(self.make_expr())
+        };
+
+        if self.in_function{
+            ST::Return { value, location }
+        }
+        else {
+            ST::NoneOrError
+        }
+    }
 
+    /// make a break or a continue statement, they must be in a loop
+    fn make_loop_statement(&mut self, keyword: Keyword, location: Location) -> Statement{
+        if self.loops == 0{
+            self.err.push(Error::outside_loop(location, self.line(), keyword.to_string()));
+            self.advance();
+            return ST::NoneOrError;
+        }
+        self.advance(); // skip the keyword
+
+        match keyword {
+            Keyword::Break => ST::Break { location },
+            Keyword::Continue => ST::Continue { location },
+            Keyword::If | Keyword::Else | Keyword::Var | Keyword::Print | Keyword::True | Keyword::False | Keyword::And
+            | Keyword::Or | Keyword::Not | Keyword::While | Keyword::Fn | Keyword::Return => unreachable!("only break and continue are given by make_statement")
+        }
     }
 
+    /// make the statement of the current line
+    /// returns Statement::NoneOrError if the line is empty
+    fn make_statement(&mut self) -> Statement{
+        let location = self.location();
+
+        match self.curr {
+            Some(Token::NewLine) | None => ST::NoneOrError,
+            Some(tk) => {
+                if tk == &Token::Keyword(Keyword::Var){
+                    self.make_var_statement(location)
+                }
+                else if tk == &Token::Keyword(Keyword::Print){
+                    self.make_print_statement(location)
+                }
+                else if tk == &Token::Keyword(Keyword::If){
+                    self.make_if_statement(location)
+                }
+                else if tk == &Token::Keyword(Keyword::While){
+                    self.make_while_statement(location)
+                }
+                else if let Token::Keyword(keyword @ (Keyword::Break | Keyword::Continue)) = tk{
+                    self.make_loop_statement(*keyword, location)
+                }
+                else if tk == &Token::Keyword(Keyword::Fn){
+                    self.make_function_statement(location)
+                }
+                else if tk == &Token::Keyword(Keyword::Return){
+                    self.make_return_statement(location)
+                }
+                else if let (Token::Identifier(id), Some(Token::Assign)) = (tk, self.peek()){
+                    self.make_identifier_statement(id.clone(), location)
+                }
+                else {
+                    // a call alone on its line is a statement, its value is not used
+                    match self.make_expr() {
+                        Expr::Call { identifier, args, .. } => ST::FuncCall { identifier, args, location },
+                        value => ST::Expr { value, location }
+                    }
+                }
+            }
+        }
+    }
+
+    /// build a statement for each line
     pub fn build_tree(&mut self){
         self.advance();
 
-        if let Some(tk) = self.curr{
-            if tk == &Token::Keyword("var".into()){
-                self.statement = self.make_var_statement();
-                return;
+        while self.curr.is_some(){
+            let err_count = self.err.len();
+            let statement = self.make_statement();
+
+            // the whole line must be used by the statement
+            if let Some(tk) = self.curr{
+                if tk != &Token::NewLine{
+                    if self.err.len() == err_count{
+                        self.unexcepted(tk);
+                    }
+                    self.skip_line();
+                }
             }
-        }
 
+            // go to the next line, a } without block is skipped too
+            self.advance();
+
+            if statement != ST::NoneOrError{
+                self.statements.push(statement);
+            }
+        }
     }
 
-    pub fn result(self) -> CompilerResult<Statement>{
+    pub fn result(self) -> CompilerResult<Vec<Statement>>{
         if self.err.is_empty(){
-            Ok(self.statement)
+            Ok(self.statements)
         }
         else {
             Err(self.err)
@@ -270,6 +1002,52 @@ mod test{
 
     use super::*;
 
+    /// tokenize and parse some lines
+    fn parse_lines(lines: &str) -> CompilerResult<Vec<Statement>>{
+        let mut token = Tokenizer::new(lines, PartialLocation::testing(1));
+        token.tokenize();
+        let tokens = token.result().unwrap();
+
+        let mut parser = AbstractSyntaxTree::new(&tokens, PartialLocation::testing(1), lines);
+        parser.build_tree();
+        parser.result()
+    }
+
+    /// tokenize and parse a line
+    fn parse(line: &str) -> CompilerResult<Statement>{
+        parse_lines(line).map(|mut statements| {
+            assert_eq!(statements.len(), 1);
+            statements.remove(0)
+        })
+    }
+
+    /// tokenize and parse a line, returns the value of the var declaration
+    fn parse_value(line: &str) -> Expr{
+        match parse(line).unwrap() {
+            Statement::VarDeclaration { value, .. } => value,
+            other => panic!("excepted a var declaration, found {:?}", other)
+        }
+    }
+
+    /// the location of a statement at the beginning of a line
+    fn loc(line: u64) -> Location{
+        Location::new("test", line, 0)
+    }
+
+    /// a location on the first line of the test
+    fn at(char_pos: u32) -> Location{
+        Location::new("test", 1, char_pos)
+    }
+
+    fn bin(opr: Operator, l: Expr, r: Expr, location: Location) -> Expr{
+        Expr::BinaryExpr { opr, l: Box::new(l), r: Box::new(r), location }
+    }
+
+    /// a variable used at a line and a column of the test
+    fn id(name: &str, line: u64, char_pos: u32) -> Expr{
+        Expr::Identifier { identifier: name.into(), location: Location::new("test", line, char_pos) }
+    }
+
     #[test]
     fn var_declaration(){
         let line = String::from("var baba = \"lol\"");
@@ -295,7 +1073,7 @@ mod test{
 
         let mut parser = AbstractSyntaxTree::new(&tokens, PartialLocation::testing(0), &line);
         dbg!(parser.build_tree()); // idk why this is not private but thats cool
-        dbg!(parser.result());
+        assert!(dbg!(parser.result()).is_ok());
 
 
     }
@@ -312,11 +1090,10 @@ mod test{
 
         let mut parser = AbstractSyntaxTree::new(&tokens, PartialLocation::testing(0), &line);
         dbg!(parser.build_tree()); // idk why this is not private but thats cool
-        dbg!(parser.result());
+        assert!(dbg!(parser.result()).is_ok());
     }
 
     #[test]
-    #[should_panic]
     fn expr_unimplemented(){
         let line = String::from("var hello = \"hello wolrd\" + \"no\" + 25 * 5");
         let mut token = Tokenizer::new(&line, PartialLocation::testing(0));
@@ -327,8 +1104,379 @@ mod test{
         let tokens = result.unwrap();
 
         let mut parser = AbstractSyntaxTree::new(&tokens, PartialLocation::testing(0), &line);
-        dbg!(parser.build_tree()); // idk why this is not private but thats cool
-        dbg!(parser.result());
+        parser.build_tree();
+        let res = dbg!(parser.result()).unwrap();
+
+        let string = |val: &str| Expr::StringLitteral(val.into());
+        let expected = bin(
+            Operator::Plus,
+            bin(Operator::Plus, string("hello wolrd"), string("no"), Location::new("test", 0, 26)),
+            bin(Operator::Mul, Expr::IntLitteral(25), Expr::IntLitteral(5), Location::new("test", 0, 38)),
+            Location::new("test", 0, 33)
+        );
+        match &res[..] {
+            [Statement::VarDeclaration { value, .. }] => assert_eq!(value, &expected),
+            other => panic!("excepted a var declaration, found {:?}", other)
+        }
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn expr_precedence(){
+        use Expr::IntLitteral as I;
+
+        // * binds tighter than +
+        assert_eq!(parse_value("var x = 1 + 2 * 3"), bin(Operator::Plus, I(1), bin(Operator::Mul, I(2), I(3), at(14)), at(10)));
+        assert_eq!(parse_value("var x = 1.5 * 2"), bin(Operator::Mul, Expr::FloatLitteral(1.5), I(2), at(12)));
+
+        // operators of the same precedence are left-associative
+        assert_eq!(parse_value("var x = 1 - 2 - 3"), bin(Operator::Minus, bin(Operator::Minus, I(1), I(2), at(10)), I(3), at(14)));
+        assert_eq!(parse_value("var x = 8 / 4 * 2"), bin(Operator::Mul, bin(Operator::Div, I(8), I(4), at(10)), I(2), at(14)));
+
+        // parenthesis can be anywhere in the chain
+        assert_eq!(
+            parse_value("var x = (10 - 2) * 6 / 4"),
+            bin(Operator::Div, bin(Operator::Mul, bin(Operator::Minus, I(10), I(2), at(12)), I(6), at(17)), I(4), at(21))
+        );
+        assert_eq!(
+            parse_value("var x = 2 * (3 + 4 * (5 - 1))"),
+            bin(Operator::Mul, I(2), bin(Operator::Plus, I(3), bin(Operator::Mul, I(4), bin(Operator::Minus, I(5), I(1), at(24)), at(19)), at(15)), at(10))
+        );
+    }
+
+    #[test]
+    fn expr_errors(){
+        // unit-type are not allowed
+        assert_eq!(parse("var x = ()").unwrap_err().len(), 1);
+
+        // missing value
+        assert_eq!(parse("var x = 25 +").unwrap_err().len(), 1);
+        assert_eq!(parse("var x = * 2").unwrap_err().len(), 1);
+
+        // a paren closed too many times, or never closed
+        assert_eq!(parse("var x = (25))").unwrap_err().len(), 1);
+        assert_eq!(parse("var hello = (\"hello wolrd\" + \"no\" + 25 * 5").unwrap_err().len(), 1);
+
+        // the parser continues after an error so every mistake of the line is reported
+        let errs = parse("var x = (1 + ) * (2 3").unwrap_err();
+        assert_eq!(errs.len(), 3);
+
+        // the keywords are named in the errors
+        let errs = parse("var x = print").unwrap_err();
+        assert!(errs[0].to_string().contains("Unexpected Token [keyword print]"));
+    }
+
+    #[test]
+    fn expr_identifier(){
+        use Expr::IntLitteral as I;
+
+        // the location of an identifier is the one of its name
+        assert_eq!(parse_value("var y = x"), id("x", 1, 8));
+        assert_eq!(parse_value("var y = x + 1"), bin(Operator::Plus, id("x", 1, 8), I(1), at(10)));
+        assert_eq!(parse_value("var y = 2 * x"), bin(Operator::Mul, I(2), id("x", 1, 12), at(10)));
+        assert_eq!(
+            parse_value("var x = (a - 2) * b / 4"),
+            bin(Operator::Div, bin(Operator::Mul, bin(Operator::Minus, id("a", 1, 9), I(2), at(11)), id("b", 1, 18), at(16)), I(4), at(20))
+        );
+
+        // two identifiers need an operator between them
+        assert!(parse("var y = x z").is_err());
+    }
+
+    #[test]
+    fn print_statement(){
+        assert_eq!(parse("print x").unwrap(), Statement::Print { value: id("x", 1, 6), location: loc(1) });
+        assert_eq!(
+            parse("print 1 + 2").unwrap(),
+            Statement::Print { value: bin(Operator::Plus, Expr::IntLitteral(1), Expr::IntLitteral(2), at(8)), location: loc(1) }
+        );
+
+        assert!(parse("print").is_err());
+    }
+
+    #[test]
+    fn var_edit_statement(){
+        assert_eq!(parse("x = 12").unwrap(), Statement::VarEdit { identifier: "x".into(), value: Expr::IntLitteral(12), location: loc(1) });
+
+        assert!(parse("x 12").is_err());
+        assert!(parse("x =").is_err());
+    }
+
+    #[test]
+    fn expr_statement(){
+        assert_eq!(parse("x").unwrap(), Statement::Expr { value: id("x", 1, 0), location: loc(1) });
+        assert_eq!(
+            parse("(1) + x").unwrap(),
+            Statement::Expr { value: bin(Operator::Plus, Expr::IntLitteral(1), id("x", 1, 6), at(4)), location: loc(1) }
+        );
+
+        assert!(parse("= 2").is_err());
+        assert!(parse(") + 1").is_err());
+    }
+
+    #[test]
+    fn func_call_statement(){
+        assert_eq!(parse("f()").unwrap(), Statement::FuncCall { identifier: "f".into(), args: vec![], location: loc(1) });
+        assert_eq!(
+            parse("f(1, x * (2 + 3), \"s\")").unwrap(),
+            Statement::FuncCall {
+                identifier: "f".into(),
+                args: vec![
+                    Expr::IntLitteral(1),
+                    bin(Operator::Mul, id("x", 1, 5), bin(Operator::Plus, Expr::IntLitteral(2), Expr::IntLitteral(3), at(12)), at(7)),
+                    Expr::StringLitteral("s".into())
+                ],
+                location: loc(1)
+            }
+        );
+
+        assert!(parse("f(1, 2").is_err());
+        assert!(parse("f(1,)").is_err());
+        assert!(parse("f(1 2)").is_err());
+        assert!(parse("f(1)(2)").is_err());
+    }
+
+    #[test]
+    fn multiple_statements(){
+        let statements = parse_lines("var x = 25\n\nx = x +\n  * 12\nprint x").unwrap_err();
+        // `x +` is not finished, the statement ends with the line
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].to_string().contains("at 3:"));
+        assert!(statements[1].to_string().contains("at 4:"));
+
+        let statements = parse_lines("var x = 25\n\nx = 12\n\n").unwrap();
+        assert_eq!(statements, vec![
+            Statement::VarDeclaration { identifier: "x".into(), ty: None, value: Expr::IntLitteral(25), location: loc(1) },
+            Statement::VarEdit { identifier: "x".into(), value: Expr::IntLitteral(12), location: loc(3) },
+        ]);
+
+        // a mistake on a line does not hide the mistakes of the next lines
+        let errs = parse_lines("var = 2\nprint (1\nf(1 2)").unwrap_err();
+        assert_eq!(errs.len(), 3);
+        assert!(errs[2].to_string().contains("at 3:"));
+    }
+
+    #[test]
+    fn error_location(){
+        // the errors point at the offending token
+        let errs = parse("var x = 1 + )").unwrap_err();
+        assert!(errs[0].to_string().contains("in test at 1:12"));
+
+        let errs = parse("var = 2").unwrap_err();
+        assert!(errs[0].to_string().contains("in test at 1:4"));
+
+        // a missing token is after the last one
+        let errs = parse("f(1, 2").unwrap_err();
+        assert!(errs[0].to_string().contains("in test at 1:6"));
+
+        let errs = parse_lines("print 1\nprint (2 +\nprint 3").unwrap_err();
+        assert!(errs[0].to_string().contains("in test at 2:10"));
+
+        // a statement begins at its first token
+        let statements = parse_lines("print 1\n  print 2").unwrap();
+        assert_eq!(statements[1].location(), Some(&Location::new("test", 2, 2)));
+    }
+
+    #[test]
+    fn unary_minus(){
+        use Expr::IntLitteral as I;
+        let neg = |value: Expr, char_pos| Expr::UnaryExpr { opr: UnaryOperator::Neg, value: Box::new(value), location: at(char_pos) };
+
+        assert_eq!(parse_value("var x = -5"), neg(I(5), 8));
+        assert_eq!(parse_value("var x = -a * b"), bin(Operator::Mul, neg(id("a", 1, 9), 8), id("b", 1, 13), at(11)));
+        assert_eq!(parse_value("var x = 1 - -2"), bin(Operator::Minus, I(1), neg(I(2), 12), at(10)));
+        assert_eq!(parse_value("var x = --2"), neg(neg(I(2), 9), 8));
+        assert_eq!(
+            parse_value("var x = -(a + b)"),
+            neg(bin(Operator::Plus, id("a", 1, 10), id("b", 1, 14), at(12)), 8)
+        );
+
+        assert!(parse("var x = -").is_err());
+        assert!(parse("var x = 2 * -").is_err());
+
+        // the smallest int can be written, its magnitude alone is too big
+        assert_eq!(parse_value("var x = -9223372036854775808"), I(i64::MIN));
+        assert_eq!(parse_value("var x = 1 - -9223372036854775808"), bin(Operator::Minus, I(1), I(i64::MIN), at(10)));
+        assert_eq!(parse_value("var x = --9223372036854775808"), neg(I(i64::MIN), 8));
+        let errs = parse("var x = 9223372036854775808").unwrap_err();
+        assert!(errs[0].to_string().contains("IntegerOverflow in test at 1:8"));
+        assert!(parse("var x = 1 -9223372036854775808").is_err());
+    }
+
+    #[test]
+    fn if_statement(){
+        let print = |value: Expr, line: u64, char_pos: u32| Statement::Print { value, location: Location::new("test", line, char_pos) };
+
+        let statements = parse_lines("if x {\n    print 1\n\n    print 2\n} else if y { print 3 } else {\n}\nprint 4").unwrap();
+        assert_eq!(statements, vec![
+            Statement::If {
+                condition: id("x", 1, 3),
+                body: Expr::Block { code: vec![print(Expr::IntLitteral(1), 2, 4), print(Expr::IntLitteral(2), 4, 4)] },
+                else_body: Some(Expr::Block { code: vec![Statement::If {
+                    condition: id("y", 5, 10),
+                    body: Expr::Block { code: vec![print(Expr::IntLitteral(3), 5, 14)] },
+                    else_body: Some(Expr::Block { code: vec![] }),
+                    location: Location::new("test", 5, 7)
+                }] }),
+                location: loc(1)
+            },
+            print(Expr::IntLitteral(4), 7, 0)
+        ]);
+
+        // the blocks can be nested
+        let statements = parse_lines("if a { if b {\n    print 1\n} }").unwrap();
+        assert_eq!(statements.len(), 1);
+
+        assert_eq!(parse_lines("if x print 1").unwrap_err().len(), 1);
+        assert_eq!(parse_lines("if x {\n    print 1").unwrap_err().len(), 1);
+        assert_eq!(parse_lines("if x { print 1 } else print 2").unwrap_err().len(), 1);
+        assert_eq!(parse_lines("else { print 1 }").unwrap_err().len(), 1);
+        assert_eq!(parse_lines("print 1 }").unwrap_err().len(), 1);
+    }
+
+    #[test]
+    fn block_errors(){
+        // the mistakes in a block are reported and the block continues
+        let errs = parse_lines("if x {\n    print (1\n    var = 2\n    print 3\n}\nprint 1 +").unwrap_err();
+        assert_eq!(errs.len(), 3);
+        assert!(errs[0].to_string().contains("at 2:"));
+        assert!(errs[1].to_string().contains("at 3:"));
+        assert!(errs[2].to_string().contains("at 6:"));
+
+        // the } after a mistake still closes the block
+        let errs = parse_lines("if x { print ) }\nprint 1").unwrap_err();
+        assert_eq!(errs.len(), 1);
+    }
+
+    #[test]
+    fn comparisons(){
+        use Expr::IntLitteral as I;
+
+        // the comparisons are below the arithmetic, and == below <
+        assert_eq!(
+            parse_value("var x = a + 1 < b * 2 == c"),
+            bin(Operator::Equal,
+                bin(Operator::Less, bin(Operator::Plus, id("a", 1, 8), I(1), at(10)), bin(Operator::Mul, id("b", 1, 16), I(2), at(18)), at(14)),
+                id("c", 1, 25), at(22))
+        );
+        assert_eq!(parse_value("var x = a >= b != c <= d"), bin(Operator::NotEqual,
+            bin(Operator::GreaterEqual, id("a", 1, 8), id("b", 1, 13), at(10)), bin(Operator::LessEqual, id("c", 1, 18), id("d", 1, 23), at(20)), at(15)));
+        assert_eq!(parse_value("var x = 1 > 2 > 3"), bin(Operator::Greater, bin(Operator::Greater, I(1), I(2), at(10)), I(3), at(14)));
+
+        // == is not an assignment
+        assert_eq!(parse("x == 1").unwrap(), Statement::Expr { value: bin(Operator::Equal, id("x", 1, 0), I(1), at(2)), location: loc(1) });
+        assert!(parse("var x == 1").is_err());
+    }
+
+    #[test]
+    fn bools(){
+        let not = |value: Expr, char_pos| Expr::UnaryExpr { opr: UnaryOperator::Not, value: Box::new(value), location: at(char_pos) };
+
+        assert_eq!(parse_value("var x = true"), Expr::BoolLitteral(true));
+        assert_eq!(parse_value("var x = false"), Expr::BoolLitteral(false));
+
+        // or < and < not < the comparisons
+        assert_eq!(parse_value("var x = a or b and c"), bin(Operator::Or, id("a", 1, 8), bin(Operator::And, id("b", 1, 13), id("c", 1, 19), at(15)), at(10)));
+        assert_eq!(parse_value("var x = not a == b"), not(bin(Operator::Equal, id("a", 1, 12), id("b", 1, 17), at(14)), 8));
+        assert_eq!(parse_value("var x = not a and b"), bin(Operator::And, not(id("a", 1, 12), 8), id("b", 1, 18), at(14)));
+        assert_eq!(parse_value("var x = a and not not b"), bin(Operator::And, id("a", 1, 8), not(not(id("b", 1, 22), 18), 14), at(10)));
+
+        assert!(parse("var x = a and").is_err());
+        assert!(parse("var x = not").is_err());
+        assert!(parse("var true = 1").is_err());
+    }
+
+    #[test]
+    fn while_statement(){
+        let statements = parse_lines("while x < 3 {\n    if x == 1 { break }\n    continue\n}").unwrap();
+        assert_eq!(statements, vec![Statement::While {
+            condition: bin(Operator::Less, id("x", 1, 6), Expr::IntLitteral(3), at(8)),
+            body: Expr::Block { code: vec![
+                Statement::If {
+                    condition: bin(Operator::Equal, id("x", 2, 7), Expr::IntLitteral(1), Location::new("test", 2, 9)),
+                    body: Expr::Block { code: vec![Statement::Break { location: Location::new("test", 2, 16) }] },
+                    else_body: None,
+                    location: Location::new("test", 2, 4)
+                },
+                Statement::Continue { location: Location::new("test", 3, 4) }
+            ] },
+            location: loc(1)
+        }]);
+
+        // break and continue must be in a loop
+        let errs = parse_lines("break\nif true {\n    continue\n}\nwhile true { }\nbreak").unwrap_err();
+        assert_eq!(errs.len(), 3);
+        assert!(errs[0].to_string().contains("OutsideLoop in test at 1:0"));
+        assert!(errs[1].to_string().contains("in test at 3:4\n|\t    continue"));
+        assert!(errs[2].to_string().contains("[break] can only be used in a loop"));
+
+        assert_eq!(parse_lines("while x print 1").unwrap_err().len(), 1);
+        assert_eq!(parse_lines("while true { break 1 }").unwrap_err().len(), 1);
+    }
+
+    #[test]
+    fn functions(){
+        let statements = parse_lines("fn add(a, b) {\n    return a + b\n}\nprint add(1, add(2, 3))\nf()").unwrap();
+        assert_eq!(statements, vec![
+            Statement::FuncDeclaration {
+                identifier: "add".into(),
+                params: vec![
+                    Param { identifier: "a".into(), ty: None, location: Location::new("test", 1, 7) },
+                    Param { identifier: "b".into(), ty: None, location: Location::new("test", 1, 10) }
+                ],
+                returns: None,
+                body: Expr::Block { code: vec![Statement::Return {
+                    value: Some(bin(Operator::Plus, id("a", 2, 11), id("b", 2, 15), Location::new("test", 2, 13))),
+                    location: Location::new("test", 2, 4)
+                }] },
+                location: loc(1)
+            },
+            Statement::Print {
+                value: Expr::Call { identifier: "add".into(), args: vec![
+                    Expr::IntLitteral(1),
+                    Expr::Call { identifier: "add".into(), args: vec![Expr::IntLitteral(2), Expr::IntLitteral(3)], location: Location::new("test", 4, 13) }
+                ], location: Location::new("test", 4, 6) },
+                location: loc(4)
+            },
+            Statement::FuncCall { identifier: "f".into(), args: vec![], location: loc(5) }
+        ]);
+
+        // return without a value
+        let statements = parse_lines("fn f() { return }").unwrap();
+        assert!(matches!(&statements[0], Statement::FuncDeclaration { body: Expr::Block { code }, .. }
+            if code == &vec![Statement::Return { value: None, location: Location::new("test", 1, 9) }]));
+
+        let errs = parse_lines("return 1").unwrap_err();
+        assert!(errs[0].to_string().contains("OutsideFunction in test at 1:0"));
+        let errs = parse_lines("if true {\n    fn f() { }\n}").unwrap_err();
+        assert!(errs[0].to_string().contains("NestedFunction in test at 2:4"));
+        // a loop around a function does not allow break in it
+        assert!(parse_lines("fn f() { break }").is_err());
+        assert!(parse_lines("fn f(a, ) { }").is_err());
+        assert!(parse_lines("fn f(1) { }").is_err());
+        assert!(parse_lines("fn { }").is_err());
+    }
+
+    #[test]
+    fn annotations(){
+        assert_eq!(parse("var x: float = 1.5").unwrap(), Statement::VarDeclaration {
+            identifier: "x".into(), ty: Some(Type::Float), value: Expr::FloatLitteral(1.5), location: loc(1)
+        });
+
+        let statements = parse_lines("fn f(s: string, n) -> int {\n    return n\n}").unwrap();
+        assert!(matches!(&statements[0], Statement::FuncDeclaration { params, returns: Some(Type::Int), .. }
+            if params == &vec![
+                Param { identifier: "s".into(), ty: Some(Type::String), location: Location::new("test", 1, 5) },
+                Param { identifier: "n".into(), ty: None, location: Location::new("test", 1, 16) }
+            ]));
+
+        let errs = parse_lines("var x: integer = 1").unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("UnknownType in test at 1:7"));
+        assert!(parse_lines("var x: = 1").is_err());
+        assert!(parse_lines("var x int = 1").is_err());
+        assert!(parse_lines("fn f(a: bool, b:) { }").is_err());
+        assert!(parse_lines("fn f() -> { }").is_err());
+        assert!(parse_lines("fn f() -> void { }").is_err());
+    }
+
+}
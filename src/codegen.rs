@@ -0,0 +1,723 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use crate::{ast::{Expr, Operator, Param, Statement, Type, UnaryOperator}, errors::Location, resolver::{Resolutions, Symbol}, typechecker::{Context, Types}};
+
+
+/// the runtime included at the beginning of every generated file
+/// Note: the strings made by a concatenation are never freed, a program does not live long enough for it to matter
+pub const RUNTIME: &str = r#"#include <limits.h>
+#include <stdbool.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+void lang_error(const char *message){
+    fprintf(stderr, "runtime error: %s\n", message);
+    exit(1);
+}
+
+/* the operations on int are checked, an overflow stops the program like in the interpreter */
+long long lang_add(long long l, long long r){
+    if ((r > 0 && l > LLONG_MAX - r) || (r < 0 && l < LLONG_MIN - r)){
+        lang_error("integer overflow");
+    }
+    return l + r;
+}
+
+long long lang_sub(long long l, long long r){
+    if ((r < 0 && l > LLONG_MAX + r) || (r > 0 && l < LLONG_MIN + r)){
+        lang_error("integer overflow");
+    }
+    return l - r;
+}
+
+long long lang_mul(long long l, long long r){
+    int overflow;
+    if (l > 0){
+        overflow = r > 0 ? l > LLONG_MAX / r : r < LLONG_MIN / l;
+    }
+    else {
+        overflow = r > 0 ? l < LLONG_MIN / r : (l != 0 && r < LLONG_MAX / l);
+    }
+    if (overflow){
+        lang_error("integer overflow");
+    }
+    return l * r;
+}
+
+long long lang_div(long long l, long long r){
+    if (r == 0){
+        lang_error("division by zero");
+    }
+    if (l == LLONG_MIN && r == -1){
+        lang_error("integer overflow");
+    }
+    return l / r;
+}
+
+long long lang_neg(long long value){
+    if (value == LLONG_MIN){
+        lang_error("integer overflow");
+    }
+    return -value;
+}
+
+/* a string knows its length, so it can contain \0 like the strings of the interpreter */
+typedef struct {
+    const char *data;
+    size_t len;
+} lang_string;
+
+lang_string lang_str(const char *data, size_t len){
+    lang_string res = {data, len};
+    return res;
+}
+
+/* the length of a litteral is known by C, even if it contains \0 */
+#define LANG_STR(litteral) lang_str(litteral, sizeof(litteral) - 1)
+
+lang_string lang_concat(lang_string l, lang_string r){
+    char *res = malloc(l.len + r.len + 1);
+    if (res == NULL){
+        lang_error("out of memory");
+    }
+    memcpy(res, l.data, l.len);
+    memcpy(res + l.len, r.data, r.len);
+    res[l.len + r.len] = '\0';
+    return lang_str(res, l.len + r.len);
+}
+
+/* compare two strings byte by byte, a string is smaller than the strings which start with it */
+int lang_compare(lang_string l, lang_string r){
+    int res = memcmp(l.data, r.data, l.len < r.len ? l.len : r.len);
+    if (res != 0){
+        return res;
+    }
+    return (l.len > r.len) - (l.len < r.len);
+}
+
+void lang_print(lang_string value){
+    fwrite(value.data, 1, value.len, stdout);
+    putchar('\n');
+}
+
+double lang_div_float(double l, double r){
+    if (r == 0.0){
+        lang_error("division by zero");
+    }
+    return l / r;
+}
+
+"#;
+
+
+/// the C declaration of a variable
+fn c_declaration(ty: Type, name: &str) -> String{
+    match ty {
+        Type::Int => format!("long long {name}"),
+        Type::Float => format!("double {name}"),
+        Type::String => format!("lang_string {name}"),
+        Type::Bool => format!("bool {name}")
+    }
+}
+
+/// write a string as a C string litteral
+/// Every character which is not printable ASCII is written as an octal escape, so the output does not depend on the encoding of the C compiler
+/// The litteral is given to LANG_STR by the generated code, so a \0 does not end the string
+pub fn c_string(string: &str) -> String{
+    let mut res = String::from("\"");
+
+    for byte in string.bytes(){
+        match byte {
+            b'"' => res.push_str("\\\""),
+            b'\\' => res.push_str("\\\\"),
+            b'\n' => res.push_str("\\n"),
+            b'\t' => res.push_str("\\t"),
+            b'\r' => res.push_str("\\r"),
+            b' '..=b'~' => res.push(byte as char),
+            _ => res.push_str(&format!("\\{:03o}", byte))
+        }
+    }
+
+    res.push('"');
+    res
+}
+
+
+/// a C function made from a function of the language for some types of arguments
+struct Instance{
+    args: Vec<Type>,
+    name: String,
+    returns: Option<Type> // None if it returns nothing
+}
+
+/// a function of the language
+/// It is translated when it is called: a C function is made for each list of argument types it is called with,
+/// like a variable declared again gets a new C variable.
+struct Function{
+    params: Vec<Param>,
+    body: Expr,
+    instances: Vec<Instance>
+}
+
+
+/// Translate the statements checked by the Resolver and the TypeChecker to a C file.
+/// Each variable of the language is a local variable of `main` or of a C function, it has the type of the value it is declared with.
+/// A block of the language is a block in C, so its variables are local to it.
+pub struct CodeGen{
+    variables: HashMap<Symbol, String>, // the C name of the variables of the function which is translated
+    declarations: HashMap<String, u32>, // the number of time each variable was declared
+    functions: HashMap<String, Function>,
+    body: String,
+    prototypes: String,
+    definitions: String, // the C functions
+    indent: usize, // the number of blocks around the current line
+    resolutions: Resolutions,
+    types: Types,
+    context: Context // the function and the types of arguments which are translated, to find the types of its expressions
+}
+
+impl CodeGen{
+    pub fn new() -> Self{
+        CodeGen {
+            variables: HashMap::new(),
+            declarations: HashMap::new(),
+            functions: HashMap::new(),
+            body: String::new(),
+            prototypes: String::new(),
+            definitions: String::new(),
+            indent: 1, // inside main
+            resolutions: Resolutions::default(),
+            types: Types::default(),
+            context: None
+        }
+    }
+
+    /// write a line in the body of main
+    fn line(&mut self, line: &str){
+        self.body.push_str(&"    ".repeat(self.indent));
+        self.body.push_str(line);
+        self.body.push('\n');
+    }
+
+    /// the symbol of the name at `location`
+    fn symbol(&self, location: &Location) -> Symbol{
+        self.resolutions.symbol(location).expect("the variables are checked by the Resolver")
+    }
+
+    /// the C name of the variable used at `location`
+    fn variable(&self, location: &Location) -> &str{
+        &self.variables[&self.symbol(location)]
+    }
+
+    /// give a C name to the variable declared at `location`, returns its C declaration
+    /// A variable declared again gets a new name, so its type can change
+    fn declare(&mut self, identifier: &str, location: &Location) -> String{
+        let count = self.declarations.entry(identifier.into()).or_insert(0);
+        let name = match count {
+            0 => format!("v_{identifier}"), // an identifier is alphanumeric so it can't contain a _
+            _ => format!("v_{identifier}_{count}")
+        };
+        *count += 1;
+
+        let symbol = self.symbol(location);
+        let declaration = c_declaration(self.types.variable(&self.context, symbol), &name);
+        self.variables.insert(symbol, name);
+        declaration
+    }
+
+    /// translate an expression
+    /// returns the C code and the type of the expression
+    fn expr(&mut self, expr: &Expr) -> (String, Type){
+        match expr {
+            // -9223372036854775808LL would be the negation of a litteral too big for a long long
+            Expr::IntLitteral(i64::MIN) => ("(-9223372036854775807LL - 1)".into(), Type::Int),
+            Expr::IntLitteral(val) => (format!("{val}LL"), Type::Int),
+
+            // the debug format always has a dot or an exponent, so C reads a double
+            Expr::FloatLitteral(val) => (format!("{val:?}"), Type::Float),
+
+            Expr::StringLitteral(val) => (format!("LANG_STR({})", c_string(val)), Type::String),
+
+            Expr::BoolLitteral(val) => (val.to_string(), Type::Bool),
+
+            Expr::Identifier { location, .. } => (self.variable(location).into(), self.types.expr(&self.context, expr)),
+
+            Expr::Call { identifier, args, .. } => {
+                let (call, _) = self.call(identifier, args);
+                (call, self.types.expr(&self.context, expr))
+            },
+
+            Expr::BinaryExpr { opr, l, r, .. } => {
+                let (l, l_ty) = self.expr(l);
+                let (r, r_ty) = self.expr(r);
+                let ty = self.types.expr(&self.context, expr);
+
+                let code = match (opr, l_ty, r_ty) {
+                    // like in the language, && and || only evaluate their right operand if it is needed
+                    (Operator::And, _, _) => format!("({l} && {r})"),
+                    (Operator::Or, _, _) => format!("({l} || {r})"),
+
+                    (opr, Type::String, Type::String) if opr.is_comparison() => format!("(lang_compare({l}, {r}) {opr} 0)"),
+                    (opr, _, _) if opr.is_comparison() => format!("({l} {opr} {r})"),
+
+                    // + is the only arithmetic operator between two strings
+                    (_, Type::String, Type::String) => format!("lang_concat({l}, {r})"),
+
+                    (_, Type::Int, Type::Int) => {
+                        let function = match opr {
+                            Operator::Plus => "lang_add",
+                            Operator::Minus => "lang_sub",
+                            Operator::Mul => "lang_mul",
+                            Operator::Div => "lang_div",
+                            _ => unreachable!("the comparisons and the logical operators are translated before")
+                        };
+                        format!("{function}({l}, {r})")
+                    },
+
+                    // an int used with a float is converted by C
+                    (Operator::Div, _, _) => format!("lang_div_float({l}, {r})"),
+                    _ => format!("({l} {opr} {r})")
+                };
+                (code, ty)
+            },
+
+            Expr::UnaryExpr { opr, value, .. } => {
+                let (value, value_ty) = self.expr(value);
+                let code = match (opr, value_ty) {
+                    (UnaryOperator::Neg, Type::Int) => format!("lang_neg({value})"),
+                    (UnaryOperator::Neg, _) => format!("(-{value})"),
+                    (UnaryOperator::Not, _) => format!("(!{value})")
+                };
+                (code, self.types.expr(&self.context, expr))
+            },
+
+            Expr::Block { .. } | Expr::Error => unreachable!("a block or an error is never an expression of a valid program")
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement){
+        match statement {
+            Statement::VarDeclaration { identifier, value, location, .. } => {
+                // the TypeChecker checked that the value has the type of the annotation
+                let (value, _) = self.expr(value);
+                let declaration = self.declare(identifier, location);
+                self.line(&format!("{declaration} = {value};"));
+            },
+
+            Statement::VarEdit { value, location, .. } => {
+                let (value, _) = self.expr(value);
+                let name = self.variable(location);
+                self.line(&format!("{name} = {value};"));
+            },
+
+            Statement::Print { value, .. } => {
+                let (value, ty) = self.expr(value);
+                let line = match ty {
+                    Type::Int => format!("printf(\"%lld\\n\", {value});"),
+                    Type::Float => format!("printf(\"%g\\n\", {value});"),
+                    Type::String => format!("lang_print({value});"),
+                    Type::Bool => format!("printf(\"%s\\n\", ({value} ? \"true\" : \"false\"));")
+                };
+                self.line(&line);
+            },
+
+            Statement::FuncCall { identifier, args, .. } => {
+                let (call, _) = self.call(identifier, args);
+                self.line(&format!("{call};"));
+            },
+
+            // the functions are declared before main, by generate
+            Statement::FuncDeclaration { .. } => (),
+
+            Statement::Return { value, .. } => {
+                match value {
+                    Some(value) => {
+                        let (value, _) = self.expr(value);
+                        self.line(&format!("return {value};"));
+                    },
+                    None => self.line("return;")
+                }
+            },
+
+            Statement::Expr { value, .. } => {
+                // the value is not used
+                let (value, _) = self.expr(value);
+                self.line(&format!("(void){value};"));
+            },
+
+            Statement::If { condition, body, else_body, .. } => {
+                self.if_statement(condition, body, else_body.as_ref(), "if");
+            },
+
+            Statement::While { condition, body, .. } => {
+                let (condition, _) = self.expr(condition);
+                self.line(&format!("while ({condition}) {{"));
+                self.block(body);
+                self.line("}");
+            },
+
+            Statement::Break { .. } => self.line("break;"),
+
+            Statement::Continue { .. } => self.line("continue;"),
+
+            Statement::NoneOrError => ()
+        }
+    }
+
+    /// translate a call, returns the C call and the type it returns, None if it returns nothing
+    /// the function is translated if it was never called with these types of arguments
+    fn call(&mut self, identifier: &str, args: &[Expr]) -> (String, Option<Type>){
+        let args = args.iter().map(|arg| self.expr(arg)).collect::<Vec<_>>();
+        let types = args.iter().map(|(_, ty)| *ty).collect::<Vec<_>>();
+
+        let function = self.functions.get(identifier).expect("the functions are checked by the Resolver");
+        let index = match function.instances.iter().position(|instance| instance.args == types) {
+            Some(index) => index,
+            None => self.instantiate(identifier, types)
+        };
+
+        let instance = &self.functions[identifier].instances[index];
+        let args = args.into_iter().map(|(arg, _)| arg).collect::<Vec<_>>().join(", ");
+        (format!("{}({args})", instance.name), instance.returns)
+    }
+
+    /// translate a function to a C function for some types of arguments
+    /// returns the index of the instance
+    fn instantiate(&mut self, identifier: &str, args: Vec<Type>) -> usize{
+        let returns = self.types.returns(identifier, &args);
+        let function = self.functions.get_mut(identifier).unwrap(); // the caller checked it
+        let index = function.instances.len();
+        let name = match index {
+            0 => format!("f_{identifier}"),
+            _ => format!("f_{identifier}_{index}")
+        };
+        function.instances.push(Instance { args: args.clone(), name: name.clone(), returns });
+        let (params, body) = (function.params.clone(), function.body.clone());
+
+        // the function is translated apart from the code which calls it
+        let variables = std::mem::take(&mut self.variables);
+        let context = self.context.replace((identifier.into(), args));
+        let declarations = std::mem::take(&mut self.declarations);
+        let caller_body = std::mem::take(&mut self.body);
+        let indent = std::mem::replace(&mut self.indent, 0);
+
+        let c_params = params.iter().map(|param| self.declare(&param.identifier, &param.location)).collect::<Vec<_>>();
+        self.block(&body);
+
+        if returns.is_some(){
+            // the end of the function is reached without return
+            self.body.push_str(&format!("    lang_error(\"the function {identifier} did not return a value\");\n"));
+        }
+
+        let c_params = if c_params.is_empty() { "void".into() } else { c_params.join(", ") };
+        let signature = match returns {
+            Some(ty) => c_declaration(ty, &format!("{name}({c_params})")),
+            None => format!("void {name}({c_params})")
+        };
+        self.prototypes.push_str(&format!("{signature};\n"));
+        self.definitions.push_str(&format!("{signature}{{\n{}}}\n\n", self.body));
+
+        self.variables = variables;
+        self.context = context;
+        self.declarations = declarations;
+        self.body = caller_body;
+        self.indent = indent;
+
+        index
+    }
+
+    /// translate a if statement, `keyword` is `if` or `} else if`
+    /// an else body with a single if statement is written as `else if`
+    fn if_statement(&mut self, condition: &Expr, body: &Expr, else_body: Option<&Expr>, keyword: &str){
+        let (condition, _) = self.expr(condition);
+        self.line(&format!("{keyword} ({condition}) {{"));
+        self.block(body);
+
+        match else_body {
+            Some(Expr::Block { code }) if matches!(code.as_slice(), [Statement::If { .. }]) => {
+                if let [Statement::If { condition, body, else_body, .. }] = code.as_slice(){
+                    self.if_statement(condition, body, else_body.as_ref(), "} else if");
+                }
+            },
+            Some(else_body) => {
+                self.line("} else {");
+                self.block(else_body);
+                self.line("}");
+            },
+            None => self.line("}")
+        }
+    }
+
+    /// translate the statements of a Expr::Block, the { and } are written by the caller
+    fn block(&mut self, block: &Expr){
+        if let Expr::Block { code } = block{
+            self.indent += 1;
+
+            for statement in code{
+                self.statement(statement);
+            }
+
+            self.indent -= 1;
+        }
+    }
+
+    /// translate the statements to a whole C file
+    /// `resolutions` and `types` are the symbols found by the Resolver and the types found by the TypeChecker for these statements
+    /// the functions are declared first, so they can be called before their declaration
+    pub fn generate(mut self, statements: &[Statement], resolutions: &Resolutions, types: &Types) -> String{
+        self.resolutions = resolutions.clone();
+        self.types = types.clone();
+        for statement in statements{
+            if let Statement::FuncDeclaration { identifier, params, body, .. } = statement{
+                self.functions.insert(identifier.clone(), Function { params: params.clone(), body: body.clone(), instances: Vec::new() });
+            }
+        }
+
+        for statement in statements{
+            self.statement(statement);
+        }
+
+        let prototypes = if self.prototypes.is_empty() { String::new() } else { format!("{}\n", self.prototypes) };
+        format!("{RUNTIME}{prototypes}{}int main(void){{\n{}    return 0;\n}}\n", self.definitions, self.body)
+    }
+}
+
+impl Default for CodeGen{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{errors::CompilerResult, program::Program};
+
+    /// translate a program to C, without computing the constant expressions first
+    fn generate(source: &str) -> CompilerResult<String>{
+        let (statements, resolutions, types) = Program::new("test", source).check()?;
+        Ok(CodeGen::new().generate(&statements, &resolutions, &types))
+    }
+
+    /// the C file excepted for the body of main
+    fn golden(body: &str) -> String{
+        format!("{RUNTIME}int main(void){{\n{body}    return 0;\n}}\n")
+    }
+
+    #[test]
+    fn grammar(){
+        assert_eq!(generate(include_str!("../grammar.txt")).unwrap(), golden(
+r#"    long long v_x = 25LL;
+    v_x = 12LL;
+    lang_string v_other = LANG_STR("Hello world");
+    printf("%lld\n", v_x);
+    lang_print(v_other);
+"#));
+    }
+
+    #[test]
+    fn expressions(){
+        assert_eq!(generate("var x = (25 + 25 - 6 * 8 / 5)\nvar t = \"x\"\nvar s = t + \"=\" + t + \"!\"").unwrap(), golden(
+r#"    long long v_x = lang_sub(lang_add(25LL, 25LL), lang_div(lang_mul(6LL, 8LL), 5LL));
+    lang_string v_t = LANG_STR("x");
+    lang_string v_s = lang_concat(lang_concat(lang_concat(v_t, LANG_STR("=")), v_t), LANG_STR("!"));
+"#));
+
+        // a string is only concatenated with a string
+        assert!(generate("var x = 1\nvar s = \"x=\" + x").is_err());
+    }
+
+    #[test]
+    fn floats(){
+        assert_eq!(generate("var x = 1.5\nvar y = x / 2 + 1\nprint y\nprint 2.0 * 3").unwrap(), golden(
+r#"    double v_x = 1.5;
+    double v_y = (lang_div_float(v_x, 2LL) + 1LL);
+    printf("%g\n", v_y);
+    printf("%g\n", (2.0 * 3LL));
+"#));
+
+        assert!(generate("var x = 1.5 - \"a\"").is_err());
+        assert!(generate("var x = 1.5 + \"a\"").is_err());
+    }
+
+    #[test]
+    fn negation(){
+        assert_eq!(generate("var x = -2\nvar y = -x * -1.5").unwrap(), golden(
+r#"    long long v_x = lang_neg(2LL);
+    double v_y = (lang_neg(v_x) * (-1.5));
+"#));
+
+        assert!(generate("var x = -\"a\"").is_err());
+    }
+
+    #[test]
+    fn comparisons(){
+        assert_eq!(generate("var x = 1 + 1 < 3\nprint 1.5 >= 2\nprint \"a\" != \"b\"").unwrap(), golden(
+r#"    bool v_x = (lang_add(1LL, 1LL) < 3LL);
+    printf("%s\n", ((1.5 >= 2LL) ? "true" : "false"));
+    printf("%s\n", ((lang_compare(LANG_STR("a"), LANG_STR("b")) != 0) ? "true" : "false"));
+"#));
+
+        assert!(generate("print \"a\" == 1").is_err());
+        assert!(generate("print true > false").is_err());
+    }
+
+    #[test]
+    fn bools(){
+        assert_eq!(generate("var t = true and not false\nprint t or 1 == 2\nprint t != false").unwrap(), golden(
+r#"    bool v_t = (true && (!false));
+    printf("%s\n", ((v_t || (1LL == 2LL)) ? "true" : "false"));
+    printf("%s\n", ((v_t != false) ? "true" : "false"));
+"#));
+
+        assert!(generate("print 1 and true").is_err());
+        assert!(generate("print not 1").is_err());
+        assert!(generate("print true * 2").is_err());
+        assert!(generate("print \"t=\" + true").is_err());
+    }
+
+    #[test]
+    fn if_else(){
+        let source = "var x = 2\nif x < 2 {\n    var y = x\n    print y\n} else if x == 2 { print 1 } else {\n    x = 3\n}\nif true { print 2 }\nvar y = 1.5";
+        assert_eq!(generate(source).unwrap(), golden(
+r#"    long long v_x = 2LL;
+    if ((v_x < 2LL)) {
+        long long v_y = v_x;
+        printf("%lld\n", v_y);
+    } else if ((v_x == 2LL)) {
+        printf("%lld\n", 1LL);
+    } else {
+        v_x = 3LL;
+    }
+    if (true) {
+        printf("%lld\n", 2LL);
+    }
+    double v_y_1 = 1.5;
+"#));
+
+        // the variables of a block can't be used after it
+        assert!(generate("if true {\n    var z = 1\n}\nprint z").is_err());
+        assert!(generate("if 1 { print 1 }").is_err());
+    }
+
+    #[test]
+    fn loops(){
+        let source = "var i = 0\nwhile i < 10 {\n    i = i + 1\n    if i == 3 { continue } else if i > 5 {\n        break\n    }\n}";
+        assert_eq!(generate(source).unwrap(), golden(
+r#"    long long v_i = 0LL;
+    while ((v_i < 10LL)) {
+        v_i = lang_add(v_i, 1LL);
+        if ((v_i == 3LL)) {
+            continue;
+        } else if ((v_i > 5LL)) {
+            break;
+        }
+    }
+"#));
+
+        assert!(generate("while 1 { }").is_err());
+    }
+
+    #[test]
+    fn functions(){
+        let source = "fn twice(x) {\n    return x + x\n}\nfn hello() { print \"hello\" }\nhello()\nprint twice(2)\nprint twice(\"a\")\nprint twice(3)";
+        let c = generate(source).unwrap();
+        assert_eq!(c, format!("{RUNTIME}{}{}int main(void){{\n{}    return 0;\n}}\n",
+r#"void f_hello(void);
+long long f_twice(long long v_x);
+lang_string f_twice_1(lang_string v_x);
+
+"#,
+r#"void f_hello(void){
+    lang_print(LANG_STR("hello"));
+}
+
+long long f_twice(long long v_x){
+    return lang_add(v_x, v_x);
+    lang_error("the function twice did not return a value");
+}
+
+lang_string f_twice_1(lang_string v_x){
+    return lang_concat(v_x, v_x);
+    lang_error("the function twice did not return a value");
+}
+
+"#,
+r#"    f_hello();
+    printf("%lld\n", f_twice(2LL));
+    lang_print(f_twice_1(LANG_STR("a")));
+    printf("%lld\n", f_twice(3LL));
+"#));
+
+        // a recursive call knows the return type after the first return
+        assert!(generate("fn fact(n) {\n    if n <= 1 { return 1 }\n    return n * fact(n - 1)\n}\nprint fact(5)").is_ok());
+        assert!(generate("fn f(n) {\n    return f(n)\n}\nprint f(1)").is_err());
+
+        assert!(generate("fn f(n) {\n    if n { return 1 }\n    return \"a\"\n}\nprint f(true)").is_err());
+        assert!(generate("fn f() { }\nprint f()").is_err());
+        assert!(generate("fn f(a) { }\nf()").is_err());
+    }
+
+    #[test]
+    fn annotations(){
+        let c = generate("var x: float = 1.5\nfn f(s: string) -> int {\n    return 1\n}").unwrap();
+        assert_eq!(c, golden("    double v_x = 1.5;\n"));
+
+        let c = generate("fn len(s: string, n: int) -> bool { return n > 0 }\nprint len(\"a\", 1)").unwrap();
+        assert!(c.contains("bool f_len(lang_string v_s, long long v_n){\n"));
+    }
+
+    #[test]
+    fn redeclaration(){
+        // the type of a variable can change when it is declared again
+        assert_eq!(generate("var x = 1\nvar x = \"one\"\nprint x").unwrap(), golden(
+r#"    long long v_x = 1LL;
+    lang_string v_x_1 = LANG_STR("one");
+    lang_print(v_x_1);
+"#));
+    }
+
+    #[test]
+    fn deterministic(){
+        let source = "var a = 1\nvar b = a + 2\nvar c = \"c\" + \"b\"\nprint c + c\nprint a * b";
+        assert_eq!(generate(source).unwrap(), generate(source).unwrap());
+    }
+
+    #[test]
+    fn errors(){
+        // undefined variable
+        assert_eq!(generate("print x").unwrap_err().len(), 1);
+        assert_eq!(generate("x = 2").unwrap_err().len(), 1);
+
+        // type mismatch
+        assert_eq!(generate("var x = \"a\" - 1").unwrap_err().len(), 1);
+        assert_eq!(generate("var x = 1\nx = \"a\"").unwrap_err().len(), 1);
+
+        // undefined function
+        assert_eq!(generate("f(1)").unwrap_err().len(), 1);
+
+        // every statement is checked
+        assert_eq!(generate("print x\nprint \"a\" * \"b\"\nprint 1").unwrap_err().len(), 2);
+    }
+
+    #[test]
+    fn string_litteral(){
+        assert_eq!(c_string("hello"), r#""hello""#);
+        assert_eq!(c_string(r"a\b"), r#""a\\b""#);
+        assert_eq!(c_string("\"\n\t"), r#""\"\n\t""#);
+        assert_eq!(c_string("é"), r#""\303\251""#);
+        // the octal escapes always have 3 digits, so a digit after them is not a part of the escape
+        assert_eq!(c_string("\x001"), r#""\0001""#);
+
+        // the escapes of the language are escaped again in C
+        assert_eq!(generate(r#"print "say \"hi\"\n\t\\ \u{e9}""#).unwrap(), golden(
+r#"    lang_print(LANG_STR("say \"hi\"\n\t\\ \303\251"));
+"#));
+
+        // the length is kept by LANG_STR, so the string does not end at \0
+        assert_eq!(generate(r#"print "a\0b""#).unwrap(), golden("    lang_print(LANG_STR(\"a\\000b\"));\n"));
+    }
+}
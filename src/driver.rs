@@ -0,0 +1,341 @@
+#![allow(dead_code)]
+
+use std::{env, fs::{self, OpenOptions}, io::{ErrorKind, Write}, path::{Path, PathBuf}, process::Command, sync::atomic::{AtomicU32, Ordering}};
+
+use crate::{program::Program, errors::{CompilerResult, Error, Location}};
+
+
+/// the C compilers which are searched in the PATH, in this order
+const COMPILERS: [&str; 4] = ["cc", "gcc", "tcc", "clang"];
+
+/// the number of temporary C files made by this process, so each build has its own file
+static TEMP_FILES: AtomicU32 = AtomicU32::new(0);
+
+
+/// The options of a build.
+/// The setters can be chained:
+/// ```
+/// BuildOptions::new("out").optimisation(2).keep_c(true)
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BuildOptions{
+    output: PathBuf,
+    optimisation: u8,
+    keep_c: bool,
+    compiler: Option<String>
+}
+
+impl BuildOptions{
+    /// create the options of a build which writes the executable at `output`
+    pub fn new(output: impl Into<PathBuf>) -> Self{
+        BuildOptions {
+            output: output.into(),
+            optimisation: 0,
+            keep_c: false,
+            compiler: None
+        }
+    }
+
+    /// edit the path of the executable
+    /// can be chained
+    pub fn output(mut self, output: impl Into<PathBuf>) -> Self{
+        self.output = output.into();
+        self
+    }
+
+    /// edit the optimisation level given to the C compiler (-O0 to -O3)
+    /// can be chained
+    pub fn optimisation(mut self, level: u8) -> Self{
+        self.optimisation = level.min(3);
+        self
+    }
+
+    /// keep the intermediate C file next to the executable, it is never written over an existing file
+    /// can be chained
+    pub fn keep_c(mut self, keep: bool) -> Self{
+        self.keep_c = keep;
+        self
+    }
+
+    /// edit the C compiler, by default it is $CC or the first compiler found in the PATH
+    /// can be chained
+    pub fn compiler(mut self, compiler: impl Into<String>) -> Self{
+        self.compiler = Some(compiler.into());
+        self
+    }
+
+    /// the path of the intermediate C file when it is kept
+    pub fn c_file(&self) -> PathBuf{
+        let mut name = self.output.clone().into_os_string();
+        name.push(".c");
+        PathBuf::from(name)
+    }
+}
+
+
+/// a new path for an intermediate C file in the temporary directory
+fn temp_c_file() -> PathBuf{
+    let id = TEMP_FILES.fetch_add(1, Ordering::Relaxed);
+    env::temp_dir().join(format!("language-{}-{id}.c", std::process::id()))
+}
+
+/// check if two paths are the same file, the paths are compared as they are if one of them does not exist
+fn same_file(a: &Path, b: &Path) -> bool{
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b
+    }
+}
+
+/// write the C code in a file which does not exist yet
+fn write_c_file(c_file: &Path, code: &str) -> std::io::Result<()>{
+    OpenOptions::new().write(true).create_new(true).open(c_file)?.write_all(code.as_bytes())
+}
+
+
+/// find the C compiler to use
+/// `explicit` is used if it is given, then $CC, then the first compiler of COMPILERS found in the PATH
+pub fn find_compiler(explicit: Option<&str>) -> Option<String>{
+    if let Some(compiler) = explicit{
+        return Some(compiler.into());
+    }
+
+    if let Ok(compiler) = env::var("CC"){
+        if !compiler.trim().is_empty(){
+            return Some(compiler);
+        }
+    }
+
+    let path = env::var_os("PATH")?;
+    COMPILERS.iter()
+        .find(|name| env::split_paths(&path).any(|dir| dir.join(name).is_file()))
+        .map(|name| name.to_string())
+}
+
+
+/// convert the output of a C compiler to errors
+/// The lines like `file.c:12:5: error: message` are located in the C file, the others are ignored.
+/// If no line can be understood, the whole output is returned as a single error.
+fn c_errors(stderr: &str, c_file: &Path, code: &str) -> Vec<Error>{
+    let filename = c_file.display().to_string();
+    let mut errs = Vec::new();
+
+    for line in stderr.lines(){
+        let rest = match line.strip_prefix(filename.as_str()).and_then(|rest| rest.strip_prefix(':')) {
+            Some(rest) => rest,
+            None => continue
+        };
+
+        let mut parts = rest.splitn(4, ':');
+        let (line_nb, char_pos, kind, message) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(line_nb), Some(char_pos), Some(kind), Some(message)) => (line_nb, char_pos, kind.trim(), message.trim()),
+            _ => continue
+        };
+
+        if kind != "error" && kind != "fatal error"{
+            continue;
+        }
+
+        let (line_nb, char_pos) = match (line_nb.parse::<u64>(), char_pos.parse::<u32>()) {
+            (Ok(line_nb), Ok(char_pos)) => (line_nb, char_pos),
+            _ => continue
+        };
+
+        errs.push(Error::c_compiler(
+            Location::new(filename.as_str(), line_nb, char_pos.saturating_sub(1)),
+            code.lines().nth((line_nb as usize).saturating_sub(1)).unwrap_or_default().to_string(),
+            message.to_string()
+        ));
+    }
+
+    if errs.is_empty(){
+        errs.push(Error::c_compiler(Location::new(filename.as_str(), 0, 0), String::new(), stderr.trim().to_string()));
+    }
+
+    errs
+}
+
+
+/// compile C code to an executable with the C compiler
+/// The C file is written in the temporary directory, or next to the executable if it is kept.
+/// returns the path of the executable
+pub fn compile_c(code: &str, options: &BuildOptions) -> CompilerResult<PathBuf>{
+    let c_file = if options.keep_c { options.c_file() } else { temp_c_file() };
+    let filename = c_file.display().to_string();
+
+    let compiler = find_compiler(options.compiler.as_deref()).ok_or_else(|| vec![
+        Error::c_compiler(Location::new(filename.as_str(), 0, 0), String::new(), "No C compiler was found, set $CC or install cc, gcc or tcc".into())
+    ])?;
+
+    write_c_file(&c_file, code).map_err(|err| vec![match err.kind() {
+        ErrorKind::AlreadyExists => Error::output_file(
+            Location::new(filename.as_str(), 0, 0), String::new(), format!("The C file [{filename}] already exists, it is not overwritten")
+        ),
+        _ => Error::c_compiler(Location::new(filename.as_str(), 0, 0), String::new(), format!("Can't write the C file: {err}"))
+    }])?;
+
+    let res = Command::new(&compiler)
+        .arg(format!("-O{}", options.optimisation))
+        .arg("-o")
+        .arg(&options.output)
+        .arg(&c_file)
+        .output();
+
+    if !options.keep_c{
+        let _ = fs::remove_file(&c_file);
+    }
+
+    let output = res.map_err(|err| vec![
+        Error::c_compiler(Location::new(filename.as_str(), 0, 0), String::new(), format!("Can't run the C compiler [{compiler}]: {err}"))
+    ])?;
+
+    if output.status.success(){
+        Ok(options.output.clone())
+    }
+    else {
+        Err(c_errors(&String::from_utf8_lossy(&output.stderr), &c_file, code))
+    }
+}
+
+
+/// translate a program to C then compile it to an executable
+/// the executable can't be written over the source file
+/// returns the path of the executable
+pub fn build(program: &Program, options: &BuildOptions) -> CompilerResult<PathBuf>{
+    if same_file(Path::new(program.filename()), &options.output){
+        return Err(vec![Error::output_file(
+            Location::new(program.filename(), 0, 0), String::new(), format!("The executable would overwrite the source file [{}]", program.filename())
+        )]);
+    }
+
+    let code = program.to_c()?;
+    compile_c(&code, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a path in the temporary directory, unique to the test
+    fn temp(name: &str) -> PathBuf{
+        env::temp_dir().join(format!("language-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn options(){
+        let options = BuildOptions::new("out").optimisation(7).keep_c(true).compiler("tcc");
+        assert_eq!(options.optimisation, 3);
+        assert!(options.keep_c);
+        assert_eq!(options.c_file(), PathBuf::from("out.c"));
+        assert_eq!(find_compiler(options.compiler.as_deref()), Some("tcc".into()));
+    }
+
+    #[test]
+    fn compiler_errors(){
+        let code = "int main(void){\n    return x;\n}\n";
+        let stderr = "out.c: In function 'main':\nout.c:2:12: error: 'x' undeclared (first use in this function)\nout.c:2:12: note: each undeclared identifier is reported only once\n";
+
+        let errs = c_errors(stderr, Path::new("out.c"), code);
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("in out.c at 2:11\n|\t    return x;"));
+
+        // an output which can't be understood is kept as it is
+        let errs = c_errors("ld: cannot find -lfoo", Path::new("out.c"), code);
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("ld: cannot find -lfoo"));
+    }
+
+    #[test]
+    fn build_grammar(){
+        if find_compiler(None).is_none(){
+            eprintln!("no C compiler, build_grammar is skipped");
+            return;
+        }
+
+        let output = temp("grammar");
+        let options = BuildOptions::new(&output).optimisation(2).keep_c(true);
+        let program = Program::new("grammar.txt", include_str!("../grammar.txt"));
+
+        let executable = build(&program, &options).unwrap();
+        assert!(options.c_file().is_file());
+
+        let res = Command::new(&executable).output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&res.stdout), "12\nHello world\n");
+
+        let _ = fs::remove_file(executable);
+        let _ = fs::remove_file(options.c_file());
+    }
+
+    #[test]
+    fn same_output_as_interpreter(){
+        if find_compiler(None).is_none(){
+            return;
+        }
+
+        let source = "var x = 7\nvar y = x / 2 * 2.5 + 0.1\nprint y\nprint 1 / 3.0\nprint 1234567.0 * 10\nprint x\nprint \"x=\" + \"7\" + \", y=\"\nprint 0.00001234\nprint -x / 2 - 1\nprint -x * -0.5\nprint \"tab\\there \\\"quoted\\\" \\u{e9}\\\\\"\nif x != 7 {\n    print 1\n} else if x > 0 and not (x > 10) {\n    var x = \"shadowed\"\n    print x\n}\nprint x\nprint \"abc\" < \"abd\"\nprint x >= 7.5 == false\nprint x < 8 or 1 / 0 > 1\nvar i = 0\nwhile true {\n    i = i + 1\n    if i == 2 { continue }\n    if i > 4 { break }\n    print i\n}\nfn fib(n) {\n    if n < 2 { return n }\n    return fib(n - 1) + fib(n - 2)\n}\nprint fib(20)\nfn greet(name) { print \"hi \" + name }\ngreet(\"you\")\ngreet(\"fib\")\nfn fact(n) {\n    if n > 1 { return n * fact(n - 1) }\n    return 1\n}\nprint fact(20)\nprint -9223372036854775807 - 1\nprint -9223372036854775808 + 1\nvar nul = \"a\\0b\"\nprint nul + \"1\"\nprint nul < \"a\\0c\" and \"a\" < \"a\\0\"\nprint \"folded \" + \"string\"\nprint (2 * 3 - 1) * 1.5 * 3 < 10 == false\nprint 1 / 3.0 * 3";
+        let program = Program::new("test", source);
+
+        let (statements, resolutions, _) = program.check().unwrap();
+        let mut interpreter = crate::interpreter::Interpreter::with_output(Vec::new());
+        interpreter.set_resolutions(resolutions);
+        interpreter.run(&statements).unwrap();
+
+        let executable = build(&program, &BuildOptions::new(temp("same-output"))).unwrap();
+        let res = Command::new(&executable).output().unwrap();
+        let _ = fs::remove_file(executable);
+
+        assert_eq!(String::from_utf8_lossy(&res.stdout), String::from_utf8_lossy(interpreter.output()));
+    }
+
+    #[test]
+    fn runtime_overflow(){
+        if find_compiler(None).is_none(){
+            return;
+        }
+
+        // like the interpreter, the executable stops when an int overflows
+        let program = Program::new("test", "var x = 9223372036854775807\nprint 1\nprint x + 1\nprint 2");
+        let executable = build(&program, &BuildOptions::new(temp("overflow"))).unwrap();
+        let res = Command::new(&executable).output().unwrap();
+        let _ = fs::remove_file(executable);
+
+        assert!(!res.status.success());
+        assert_eq!(String::from_utf8_lossy(&res.stdout), "1\n");
+        assert!(String::from_utf8_lossy(&res.stderr).contains("integer overflow"));
+    }
+
+    #[test]
+    fn build_errors(){
+        // the C compiler does not exist
+        let options = BuildOptions::new(temp("missing")).compiler("this-compiler-does-not-exist");
+        let errs = compile_c("int main(void){ return 0; }", &options).unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(!options.c_file().exists(), "the C file is not kept");
+
+        // an existing C file is not overwritten
+        let options = BuildOptions::new(temp("existing")).keep_c(true).compiler("this-compiler-does-not-exist");
+        fs::write(options.c_file(), "user file").unwrap();
+        let errs = compile_c("int main(void){ return 0; }", &options).unwrap_err();
+        assert!(errs[0].to_string().contains("OutputFileError"));
+        assert_eq!(fs::read_to_string(options.c_file()).unwrap(), "user file");
+        let _ = fs::remove_file(options.c_file());
+
+        // the executable can't replace the source
+        let source = temp("source");
+        fs::write(&source, "print 1").unwrap();
+        let program = Program::from_file(&source).unwrap();
+        let errs = build(&program, &BuildOptions::new(&source)).unwrap_err();
+        assert!(errs[0].to_string().contains("overwrite the source file"));
+        assert_eq!(fs::read_to_string(&source).unwrap(), "print 1");
+        let _ = fs::remove_file(source);
+
+        // the errors of the C compiler are reported
+        if find_compiler(None).is_none(){
+            return;
+        }
+        let options = BuildOptions::new(temp("invalid"));
+        let errs = compile_c("int main(void){\n    return x;\n}\n", &options).unwrap_err();
+        assert!(!errs.is_empty());
+        assert!(errs[0].to_string().contains("return x;"));
+    }
+}
@@ -15,12 +15,12 @@ pub enum ErrorType{
     Note
 }
 
-impl ToString for ErrorType{
-    fn to_string(&self) -> String {
+impl Display for ErrorType{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ErrorType::Error => String::from("error"),
-            ErrorType::Warning => String::from("warning"),
-            ErrorType::Note => String::from("note"),
+            ErrorType::Error => write!(f, "error"),
+            ErrorType::Warning => write!(f, "warning"),
+            ErrorType::Note => write!(f, "note"),
         }
     }
 }
@@ -57,11 +57,21 @@ impl PartialLocation{
     pub fn testing(line: u64) -> Self{
         Self { filename: "test".into(), line}
     }
+
+    /// the number of the line
+    pub fn get_line(&self) -> u64{
+        self.line
+    }
+
+    /// go to the next line of the file
+    pub fn next_line(&mut self){
+        self.line += 1;
+    }
 }
 
 /// Indicate the filename, the line and the char of an error.
 /// Its main use is for error
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct Location{
     filename: String, //no need to mutate this string
     line: u64,
@@ -160,6 +170,14 @@ impl Error{
         Self::new(ErrorType::Error, location, "StringClosingError", "A String litteral was not closed", line.into().as_str())
     }
 
+    /// create a comment closing error. It indicates that a block comment was not closed.
+    pub fn comment_closing<S>(location:Location, line: S) -> Self
+    where S: Into<String> 
+    {
+
+        Self::new(ErrorType::Error, location, "CommentClosingError", "A block comment was not closed", line.into().as_str())
+    }
+
     /// create a illegal character error. It indicates that a illegal charactrer was encountred
     pub fn illegal_character<S>(location:Location, line: S, char: char) -> Self
     where S: Into<String> 
@@ -170,6 +188,32 @@ impl Error{
           line.into().as_str())
     }
 
+    /// create a integer overflow error. It indicates that a litteral integer is too big
+    pub fn integer_overflow<S>(location:Location, line: S) -> Self
+    where S: Into<String> 
+    {
+
+        Self::new(ErrorType::Error, location, "IntegerOverflow",
+         &format!("A litteral integer can't be bigger than {}", i64::MAX),
+          line.into().as_str())
+    }
+
+    /// create an arithmetic overflow error. It indicates that the result of an operation on int is too big for an int
+    pub fn arithmetic_overflow<S>(location:Location, line: S) -> Self
+    where S: Into<String> 
+    {
+
+        Self::new(ErrorType::Error, location, "ArithmeticOverflow",
+         &format!("The result of an operation on int is not between {} and {}", i64::MIN, i64::MAX),
+          line.into().as_str())
+    }
+
+    /// create an escape sequence error. It indicates that a backslash in a string is followed by something unknown
+    pub fn escape_sequence<S>(location:Location, line: S, desc: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "EscapeSequence", desc.into().as_str(), line.into().as_str())
+    }
+
     /// create a excepted token error. It indicates that an excepted token was not found
     pub fn excepted_token<S>(location:Location, line: S, excepted: S) -> Self
     where S: Into<String> {
@@ -183,23 +227,144 @@ impl Error{
     }
 
 
-    /// create a floating number error. The compiler does not support floating number.
+    /// create a undefined variable error. It indicates that a variable is used before its declaration
+    pub fn undefined_variable<S>(location:Location, line: S, name: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "UndefinedVariable", format!("The variable [{}] is not declared", name.into()).as_str(), line.into().as_str())
+    }
+
+    /// create a undefined assignment error. It indicates that a value is assigned to a variable which is not declared
+    pub fn undefined_assignment<S>(location:Location, line: S, name: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "UndefinedVariable", format!("Can't assign [{}], it is not declared", name.into()).as_str(), line.into().as_str())
+    }
+
+    /// create a undefined function error. It indicates that a function which does not exist is called
+    pub fn undefined_function<S>(location:Location, line: S, name: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "UndefinedFunction", format!("The function [{}] does not exist", name.into()).as_str(), line.into().as_str())
+    }
+
+    /// create an outside loop error. It indicates that break or continue is used outside of a loop
+    pub fn outside_loop<S>(location:Location, line: S, keyword: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "OutsideLoop", format!("[{}] can only be used in a loop", keyword.into()).as_str(), line.into().as_str())
+    }
+
+    /// create an outside function error. It indicates that return is used outside of a function
+    pub fn outside_function<S>(location:Location, line: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "OutsideFunction", "[return] can only be used in a function", line.into().as_str())
+    }
+
+    /// create a nested function error. It indicates that a function is declared in a block
+    pub fn nested_function<S>(location:Location, line: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "NestedFunction", "A function can only be declared outside of the blocks", line.into().as_str())
+    }
+
+    /// create an arity error. It indicates that a function is called with too many or too few arguments
+    pub fn arity<S>(location:Location, line: S, name: S, excepted: usize, found: usize) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "ArityMismatch",
+            format!("The function [{}] takes {excepted} argument(s) but {found} were given", name.into()).as_str(),
+            line.into().as_str())
+    }
+
+    /// create a duplicate parameter error. It indicates that two parameters of a function have the same name
+    pub fn duplicate_parameter<S>(location:Location, line: S, name: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "DuplicateParameter", format!("The parameter [{}] is declared twice", name.into()).as_str(), line.into().as_str())
+    }
+
+    /// create a duplicate function error. It indicates that two functions of a program have the same name
+    pub fn duplicate_function<S>(location:Location, line: S, name: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "DuplicateFunction", format!("The function [{}] is declared twice", name.into()).as_str(), line.into().as_str())
+    }
+
+    /// create a no return value error. It indicates that the value of a function which returns nothing is used
+    pub fn no_return_value<S>(location:Location, line: S, name: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "NoReturnValue", format!("The function [{}] did not return a value", name.into()).as_str(), line.into().as_str())
+    }
+
+    /// create a stack overflow error. It indicates that there are too many calls in each other, an infinite recursion for example
+    pub fn stack_overflow<S>(location:Location, line: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "StackOverflow", "Too many functions are called in each other", line.into().as_str())
+    }
+
+    /// create a type mismatch error. It indicates that a value has not the excepted type
+    pub fn type_mismatch<S>(location:Location, line: S, desc: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "TypeMismatch", desc.into().as_str(), line.into().as_str())
+    }
+
+    /// create an unknown type error. It indicates that a type annotation is not a type of the language
+    pub fn unknown_type<S>(location:Location, line: S, name: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "UnknownType",
+            format!("The type [{}] does not exist, a type is int, float, string or bool", name.into()).as_str(),
+            line.into().as_str())
+    }
+
+    /// create a division by zero error. It indicates that a value was divided by zero
+    pub fn division_by_zero<S>(location:Location, line: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "DivisionByZero", "A value was divided by zero", line.into().as_str())
+    }
+
+    /// create a C compiler error. It indicates that the C compiler could not make the executable
+    pub fn c_compiler<S>(location:Location, line: S, desc: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "CCompilerError", desc.into().as_str(), line.into().as_str())
+    }
+
+    /// create an output file error. It indicates that the build would overwrite a file it must not touch
+    pub fn output_file<S>(location:Location, line: S, desc: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "OutputFileError", desc.into().as_str(), line.into().as_str())
+    }
+
+    /// create a thread error. It indicates that the thread which compiles or executes the program can't be created
+    pub fn thread<S>(location:Location, line: S, desc: S) -> Self
+    where S: Into<String> {
+        Self::new(ErrorType::Error, location, "ThreadError", desc.into().as_str(), line.into().as_str())
+    }
+
+    /// create a floating number error. It indicates that a litteral floating number is malformed, like `1.2.3` or `1.`
     pub fn floating_numer<S>(location:Location, line: S) -> Self
     where S: Into<String> 
     {
 
-        Self::new(ErrorType::Error, location, "FloatingNumber", "Floating Number are not supported.", line.into().as_str())
+        Self::new(ErrorType::Error, location, "FloatingNumber", "A floating number must be written like 1.5", line.into().as_str())
     }
 
 }
 
 
 
+impl Error{
+    /// set the line of code shown by the error if it is unknown.
+    /// The passes which come after the parser only know the location of the problem, not the code.
+    /// `first_line` is the number of the first line of `source`
+    pub fn source_line(mut self, source: &str, first_line: u64) -> Self{
+        if self.line.is_empty(){
+            if let Some(line) = source.lines().nth(self.location.line.saturating_sub(first_line) as usize){
+                self.line = line.into();
+            }
+        }
+        self
+    }
+}
+
+
 impl Display for Error{
     /// basic error output
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}: {} in {} at {}:{}\n|\t{}\n|\t{}\n{}", 
-        self.err_type.to_string(),
+        self.err_type,
         self.name,
         self.location.filename,
         self.location.line,
@@ -0,0 +1,289 @@
+#![allow(dead_code)]
+
+use std::io::Sink;
+
+use crate::{ast::{Expr, Operator, Statement}, errors::{CompilerResult, Error, Location}, interpreter::{Interpreter, Value}};
+
+
+/// Compute the expressions which only contain litterals before the execution or the translation, like `(25 + 25 - 6 * 8 / 5)`.
+/// The values are computed by the interpreter, so they are the same as at the execution.
+///
+/// A division by zero or an overflow in a constant expression is an error before the program starts, only if the expression is always computed.
+/// The code which may not be executed is lazy: its errors are not reported and the expression which fails stays as it is, so it fails if it is executed.
+/// The lazy code is:
+/// - the right operand of `and` and `or`
+/// - the body of `if`, of `else` and of `while`, the conditions are always computed
+/// - the body of a function
+///
+/// The statements must have been checked by the TypeChecker.
+pub struct ConstantFolder{
+    interpreter: Interpreter<Sink>, // computes the constant expressions, it never prints
+    lazy: u32, // the number of operands and bodies around the current expression which may not be computed
+    err: Vec<Error>
+}
+
+impl ConstantFolder{
+    pub fn new() -> Self{
+        ConstantFolder {
+            interpreter: Interpreter::with_output(std::io::sink()),
+            lazy: 0,
+            err: Vec::new()
+        }
+    }
+
+    /// compute an expression made of litterals
+    /// returns None if it can't be computed, an error is pushed if the expression is always computed
+    fn compute(&mut self, expr: Expr, location: &Location) -> Option<Expr>{
+        match self.interpreter.eval(&expr, location) {
+            Ok(value) => litteral(value),
+            Err(err) => {
+                if self.lazy == 0{
+                    self.err.push(err);
+                }
+                None
+            }
+        }
+    }
+
+    /// fold an expression which may not be computed
+    fn lazy_expr(&mut self, expr: &Expr, location: &Location) -> Expr{
+        self.lazy += 1;
+        let expr = self.expr(expr, location);
+        self.lazy -= 1;
+        expr
+    }
+
+    /// fold the constant parts of an expression
+    /// `location` is the location of the statement, used by errors
+    fn expr(&mut self, expr: &Expr, location: &Location) -> Expr{
+        match expr {
+            // a part which is not computed keeps the location of its operator, the TypeChecker found its type there
+            Expr::BinaryExpr { opr, l, r, location: opr_location } => {
+                let l = self.expr(l, location);
+                let r = if opr.is_logical() { self.lazy_expr(r, location) } else { self.expr(r, location) };
+
+                match (opr, l, r) {
+                    // the right operand gives the result, or it is not computed
+                    (Operator::And, Expr::BoolLitteral(true), r) | (Operator::Or, Expr::BoolLitteral(false), r) => r,
+                    (Operator::And, Expr::BoolLitteral(false), _) => Expr::BoolLitteral(false),
+                    (Operator::Or, Expr::BoolLitteral(true), _) => Expr::BoolLitteral(true),
+
+                    // the division fails each time it is computed, even if the left operand is not constant
+                    (Operator::Div, l, r @ (Expr::IntLitteral(0) | Expr::FloatLitteral(0.0))) if !is_constant(&l) && self.lazy == 0 => {
+                        self.err.push(Error::division_by_zero(location.clone(), String::new()));
+                        Expr::BinaryExpr { opr: Operator::Div, l: Box::new(l), r: Box::new(r), location: opr_location.clone() }
+                    },
+
+                    (opr, l, r) => {
+                        let expr = Expr::BinaryExpr { opr: opr.clone(), l: Box::new(l), r: Box::new(r), location: opr_location.clone() };
+                        if !is_constant(&expr){
+                            return expr;
+                        }
+                        self.compute(expr.clone(), location).unwrap_or(expr)
+                    }
+                }
+            },
+
+            Expr::UnaryExpr { opr, value, location: opr_location } => {
+                let expr = Expr::UnaryExpr { opr: opr.clone(), value: Box::new(self.expr(value, location)), location: opr_location.clone() };
+                if !is_constant(&expr){
+                    return expr;
+                }
+                self.compute(expr.clone(), location).unwrap_or(expr)
+            },
+
+            Expr::Call { identifier, args, location } => Expr::Call {
+                identifier: identifier.clone(),
+                args: args.iter().map(|arg| self.expr(arg, location)).collect(),
+                location: location.clone()
+            },
+
+            Expr::Block { code } => Expr::Block { code: code.iter().map(|statement| self.statement(statement)).collect() },
+
+            Expr::IntLitteral(_) | Expr::FloatLitteral(_) | Expr::StringLitteral(_) | Expr::BoolLitteral(_) | Expr::Identifier { .. } | Expr::Error => expr.clone()
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Statement{
+        match statement {
+            Statement::VarDeclaration { identifier, ty, value, location } => Statement::VarDeclaration {
+                identifier: identifier.clone(), ty: *ty, value: self.expr(value, location), location: location.clone()
+            },
+
+            Statement::VarEdit { identifier, value, location } => Statement::VarEdit {
+                identifier: identifier.clone(), value: self.expr(value, location), location: location.clone()
+            },
+
+            Statement::Print { value, location } => Statement::Print { value: self.expr(value, location), location: location.clone() },
+
+            Statement::Expr { value, location } => Statement::Expr { value: self.expr(value, location), location: location.clone() },
+
+            Statement::FuncCall { identifier, args, location } => Statement::FuncCall {
+                identifier: identifier.clone(),
+                args: args.iter().map(|arg| self.expr(arg, location)).collect(),
+                location: location.clone()
+            },
+
+            Statement::FuncDeclaration { identifier, params, returns, body, location } => Statement::FuncDeclaration {
+                identifier: identifier.clone(), params: params.clone(), returns: *returns, body: self.lazy_expr(body, location), location: location.clone()
+            },
+
+            Statement::Return { value, location } => Statement::Return {
+                value: value.as_ref().map(|value| self.expr(value, location)), location: location.clone()
+            },
+
+            Statement::If { condition, body, else_body, location } => Statement::If {
+                condition: self.expr(condition, location),
+                body: self.lazy_expr(body, location),
+                else_body: else_body.as_ref().map(|else_body| self.lazy_expr(else_body, location)),
+                location: location.clone()
+            },
+
+            Statement::While { condition, body, location } => Statement::While {
+                condition: self.expr(condition, location),
+                body: self.lazy_expr(body, location),
+                location: location.clone()
+            },
+
+            Statement::Break { .. } | Statement::Continue { .. } | Statement::NoneOrError => statement.clone()
+        }
+    }
+
+    /// fold the constant expressions of the statements
+    /// the errors don't have their line of code, the caller knows the source
+    pub fn fold(mut self, statements: &[Statement]) -> CompilerResult<Vec<Statement>>{
+        let statements = statements.iter().map(|statement| self.statement(statement)).collect();
+
+        if self.err.is_empty(){
+            Ok(statements)
+        }
+        else {
+            Err(self.err)
+        }
+    }
+}
+
+impl Default for ConstantFolder{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// check if an expression only contains litterals and operators
+fn is_constant(expr: &Expr) -> bool{
+    match expr {
+        Expr::IntLitteral(_) | Expr::FloatLitteral(_) | Expr::StringLitteral(_) | Expr::BoolLitteral(_) => true,
+        Expr::BinaryExpr { l, r, .. } => is_constant(l) && is_constant(r),
+        Expr::UnaryExpr { value, .. } => is_constant(value),
+        Expr::Identifier { .. } | Expr::Call { .. } | Expr::Block { .. } | Expr::Error => false
+    }
+}
+
+/// the litteral of a value
+/// None for an infinite or NaN float, they can't be written as a litteral
+fn litteral(value: Value) -> Option<Expr>{
+    match value {
+        Value::Int(val) => Some(Expr::IntLitteral(val)),
+        Value::Float(val) if val.is_finite() => Some(Expr::FloatLitteral(val)),
+        Value::Float(_) => None,
+        Value::String(val) => Some(Expr::StringLitteral(val)),
+        Value::Bool(val) => Some(Expr::BoolLitteral(val))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::Program;
+
+    /// parse then fold a program
+    fn fold(source: &str) -> CompilerResult<Vec<Statement>>{
+        let statements = Program::new("test", source).parse()?;
+        ConstantFolder::new().fold(&statements)
+    }
+
+    /// the value of the first statement, which must be a print
+    fn folded(source: &str) -> Expr{
+        match fold(source).unwrap().remove(0) {
+            Statement::Print { value, .. } => value,
+            other => panic!("excepted a print, found {:?}", other)
+        }
+    }
+
+    #[test]
+    fn litterals(){
+        assert_eq!(folded("print (25 + 25 - 6 * 8 / 5)"), Expr::IntLitteral(41));
+        assert_eq!(folded("print -7 / 2"), Expr::IntLitteral(-3));
+        assert_eq!(folded("print \"hello world\" + \"no\" + \"25\""), Expr::StringLitteral("hello worldno25".into()));
+        assert_eq!(folded("print \"abc\" < \"abd\" and not (3 == 4)"), Expr::BoolLitteral(true));
+        assert_eq!(folded("print 1.5 * 2"), Expr::FloatLitteral(3.0));
+        assert_eq!(folded("print -9223372036854775807 - 1"), Expr::IntLitteral(i64::MIN));
+
+        // only the constant parts are computed
+        let x = Box::new(Expr::Identifier { identifier: "x".into(), location: Location::new("test", 1, 6) });
+        assert_eq!(
+            folded("print x * (2 + 3)"),
+            Expr::BinaryExpr { opr: Operator::Mul, l: x, r: Box::new(Expr::IntLitteral(5)), location: Location::new("test", 1, 8) }
+        );
+        assert_eq!(folded("print f(1 + 1)"), Expr::Call { identifier: "f".into(), args: vec![Expr::IntLitteral(2)], location: Location::new("test", 1, 6) });
+
+        // an infinite float has no litteral
+        assert!(matches!(folded(&format!("print {}.0 * 10", "9".repeat(308))), Expr::BinaryExpr { .. }));
+    }
+
+    #[test]
+    fn logical(){
+        let x = |col| Expr::Identifier { identifier: "x".into(), location: Location::new("test", 1, col) };
+        assert_eq!(folded("print true and x"), x(15));
+        assert_eq!(folded("print false or x"), x(15));
+        assert_eq!(folded("print false and x"), Expr::BoolLitteral(false));
+        assert_eq!(folded("print true or 1 / 0 > 1"), Expr::BoolLitteral(true));
+
+        // the right operand may not be computed, so its errors are not reported
+        let expr = folded("print x or 1 / 0 > 1");
+        assert!(matches!(expr, Expr::BinaryExpr { opr: Operator::Or, .. }));
+        assert!(fold("print x and (9223372036854775807 + 1 > 0)").is_ok());
+    }
+
+    #[test]
+    fn blocks(){
+        let statements = fold("while x < 2 + 2 {\n    if 1 < 2 { x = x + 3 * 3 }\n}").unwrap();
+        assert_eq!(statements, fold("while x < 4     {\n    if true  { x = x + 9     }\n}").unwrap());
+
+        let statements = fold("fn f(a: int) -> int {\n    return a + 1 * 2\n}").unwrap();
+        assert_eq!(statements, fold("fn f(a: int) -> int {\n    return a + 2\n}").unwrap());
+    }
+
+    #[test]
+    fn errors(){
+        let errs = fold("var x = 1\nprint x / (2 - 2)\nprint 9223372036854775807 + 1").unwrap_err();
+        assert_eq!(errs.len(), 2);
+        assert!(errs[0].to_string().contains("DivisionByZero in test at 2:0"));
+        assert!(errs[1].to_string().contains("ArithmeticOverflow in test at 3:0"));
+
+        assert!(fold("print 1.5 / 0").is_err());
+        assert!(fold("print \"a\" + 1").is_err());
+        assert!(fold("print f(1) / -0.0").is_err());
+        assert!(fold("print x or y / 0 > 1").is_ok());
+        assert!(fold("print -(-9223372036854775807 - 1)").is_err());
+        assert!(fold("if x > (1 - 1) / 0 { }").is_err());
+        assert!(fold("while 9223372036854775807 + 1 > 0 { }").is_err());
+    }
+
+    #[test]
+    fn lazy(){
+        // the code which may not be executed has no errors, and the expression which fails is kept
+        let statements = fold("if x {\n    print 1 / 0\n} else {\n    print 2 / 0\n}").unwrap();
+        assert_eq!(statements, Program::new("test", "if x {\n    print 1 / 0\n} else {\n    print 2 / 0\n}").parse().unwrap());
+        assert!(fold("while x {\n    var y = x / 0\n}").is_ok());
+        assert!(fold("fn f() {\n    return 4294967296 * 4294967296\n}").is_ok());
+        assert!(fold("if x {\n    if true { print -(-9223372036854775807 - 1) }\n}").is_ok());
+
+        // the rest of the lazy code is still computed
+        let statements = fold("if x {\n    print 1 + 2\n}").unwrap();
+        assert_eq!(statements, fold("if x {\n    print 3\n}").unwrap());
+
+        // the code after a lazy body is always executed
+        assert!(fold("if x { }\nprint 1 / 0").is_err());
+    }
+}
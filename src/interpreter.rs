@@ -0,0 +1,675 @@
+#![allow(dead_code)]
+
+use std::{collections::HashMap, fmt::Display, io::{Stdout, Write}, rc::Rc};
+
+use crate::{ast::{Expr, Operator, Param, Statement, Type, UnaryOperator}, errors::{CompilerResult, Error, Location}, resolver::{Resolutions, Symbol}};
+
+
+/// a value computed by the interpreter
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value{
+    Int(i64),
+    Float(f64),
+    String(String),
+    Bool(bool)
+}
+
+impl Value{
+    pub fn ty(&self) -> Type{
+        match self {
+            Value::Int(_) => Type::Int,
+            Value::Float(_) => Type::Float,
+            Value::String(_) => Type::String,
+            Value::Bool(_) => Type::Bool
+        }
+    }
+
+    /// the value as a float, an int is converted
+    /// returns None if it is not a number
+    fn as_float(&self) -> Option<f64>{
+        match self {
+            Value::Int(val) => Some(*val as f64),
+            Value::Float(val) => Some(*val),
+            Value::String(_) | Value::Bool(_) => None
+        }
+    }
+}
+
+impl Display for Value{
+    /// the value as it is printed
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Int(val) => write!(f, "{val}"),
+            Value::Float(val) => write!(f, "{}", format_float(*val)),
+            Value::String(val) => write!(f, "{val}"),
+            Value::Bool(val) => write!(f, "{val}"),
+        }
+    }
+}
+
+
+/// remove the useless zeros after the dot, and the dot if nothing is after it
+fn trim_zeros(number: &str) -> &str{
+    if number.contains('.'){
+        number.trim_end_matches('0').trim_end_matches('.')
+    }
+    else {
+        number
+    }
+}
+
+/// write a floating number like the `%g` format of printf, so the interpreter prints the same thing than the C backend:
+/// 6 significant digits without the useless zeros, and an exponent for the very big or small numbers
+pub fn format_float(val: f64) -> String{
+    if val.is_nan(){
+        return "nan".into();
+    }
+    if val.is_infinite(){
+        return if val > 0.0 { "inf".into() } else { "-inf".into() };
+    }
+    if val == 0.0{
+        return if val.is_sign_negative() { "-0".into() } else { "0".into() };
+    }
+
+    let scientific = format!("{:.5e}", val); // like 1.23457e4
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or_default();
+
+    if (-4..6).contains(&exponent){
+        let fixed = format!("{:.*}", (5 - exponent) as usize, val);
+        trim_zeros(&fixed).into()
+    }
+    else {
+        format!("{}e{}{:02}", trim_zeros(mantissa), if exponent < 0 { '-' } else { '+' }, exponent.abs())
+    }
+}
+
+
+/// what the interpreter does after a statement
+#[derive(Clone, PartialEq, Debug)]
+enum Flow{
+    /// go to the next statement
+    Next,
+    /// leave the innermost loop
+    Break,
+    /// go to the next iteration of the innermost loop
+    Continue,
+    /// leave the function, with its value if it has one
+    Return(Option<Value>)
+}
+
+
+/// a function declared by the program
+#[derive(Debug)]
+struct Function{
+    params: Vec<Param>,
+    returns: Option<Type>,
+    body: Expr
+}
+
+/// the maximum number of blocks executed in each other when a function is called, so an infinite recursion is an error instead of a crash
+/// The body of a function is a block, so it is also the maximum number of calls in each other.
+/// The blocks are counted instead of the calls because each of them uses the stack, MAX_DEPTH of them fit in program::STACK_SIZE in a debug build.
+/// The interpreter must run on a thread made by program::with_stack.
+pub const MAX_DEPTH: usize = 1_500;
+
+
+/// Execute the statements one by one.
+/// It follows the same rules than the C backend, so a program gives the same output when it is interpreted or compiled:
+/// an operation on int which overflows is an error, an int used with a float is converted to a float
+/// and a string can only be concatenated with a string.
+/// The variables are found with the symbols of the Resolver, so the statements must have been resolved.
+/// A function only sees its parameters and its own variables, and it can be called before its declaration.
+pub struct Interpreter<W: Write = Stdout>{
+    variables: HashMap<Symbol, Value>, // the variables of the current function, or of the program outside of the functions
+    resolutions: Resolutions, // the symbol of each name of the statements
+    functions: HashMap<String, Rc<Function>>,
+    depth: usize, // the number of blocks in progress
+    out: W // where print writes
+}
+
+impl Interpreter{
+    /// create an interpreter which prints on stdout
+    pub fn new() -> Self{
+        Self::with_output(std::io::stdout())
+    }
+}
+
+impl Default for Interpreter{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Write> Interpreter<W>{
+    /// create an interpreter which prints in `out`
+    pub fn with_output(out: W) -> Self{
+        Interpreter {
+            variables: HashMap::new(),
+            resolutions: Resolutions::default(),
+            functions: HashMap::new(),
+            depth: 0,
+            out
+        }
+    }
+
+    /// where print writes
+    pub fn output(&self) -> &W{
+        &self.out
+    }
+
+    /// write a value and a new line
+    pub fn print(&mut self, value: &Value){
+        // like printf, nothing can be done if stdout is closed
+        let _ = writeln!(self.out, "{value}");
+    }
+
+    /// use the symbols found by the Resolver for the next statements
+    /// the resolutions must contain the ones of the statements already executed, the functions declared before use them
+    pub fn set_resolutions(&mut self, resolutions: Resolutions){
+        self.resolutions = resolutions;
+    }
+
+    /// the symbol of the name at `location`
+    fn symbol(&self, identifier: &str, location: &Location) -> Result<Symbol, Error>{
+        self.resolutions.symbol(location).ok_or_else(|| Error::undefined_variable(location.clone(), String::new(), identifier.into()))
+    }
+
+    /// compute the value of an expression
+    /// `location` is the location of the statement, used by errors
+    pub fn eval(&mut self, expr: &Expr, location: &Location) -> Result<Value, Error>{
+        match expr {
+            Expr::IntLitteral(val) => Ok(Value::Int(*val)),
+
+            Expr::FloatLitteral(val) => Ok(Value::Float(*val)),
+
+            Expr::StringLitteral(val) => Ok(Value::String(val.clone())),
+
+            Expr::BoolLitteral(val) => Ok(Value::Bool(*val)),
+
+            Expr::Identifier { identifier, location } => {
+                let symbol = self.symbol(identifier, location)?;
+                self.variables.get(&symbol).cloned().ok_or_else(|| Error::undefined_variable(location.clone(), String::new(), identifier.clone()))
+            },
+
+            Expr::Call { identifier, args, location } => {
+                self.call(identifier, args, location)?.ok_or_else(|| Error::no_return_value(location.clone(), String::new(), identifier.clone()))
+            },
+
+            Expr::BinaryExpr { opr, l, r, .. } if opr.is_logical() => {
+                // the right operand is only evaluated if the left one does not give the result
+                let what = format!("The operands of [{opr}]");
+                let l = self.eval_bool(l, location, &what)?;
+                if l == (opr == &Operator::Or){
+                    return Ok(Value::Bool(l));
+                }
+                self.eval_bool(r, location, &what).map(Value::Bool)
+            },
+
+            Expr::BinaryExpr { opr, l, r, .. } => {
+                let l = self.eval(l, location)?;
+                let r = self.eval(r, location)?;
+
+                match (opr, l, r) {
+                    (opr, l, r) if opr.is_comparison() => {
+                        let res = match (&l, &r) {
+                            (Value::Int(l), Value::Int(r)) => opr.compare(l, r),
+                            (Value::String(l), Value::String(r)) => opr.compare(l, r), // byte by byte, like strcmp
+                            // two bools can only be equal or not
+                            (Value::Bool(l), Value::Bool(r)) if matches!(opr, Operator::Equal | Operator::NotEqual) => opr.compare(l, r),
+                            _ => l.as_float().zip(r.as_float()).and_then(|(l, r)| opr.compare(&l, &r))
+                        };
+
+                        res.map(Value::Bool).ok_or_else(|| Error::type_mismatch(
+                            location.clone(), String::new(),
+                            format!("The operator [{opr}] can't be used between {} and {}", l.ty(), r.ty())
+                        ))
+                    },
+
+                    (opr, Value::Int(l), Value::Int(r)) => {
+                        if opr == &Operator::Div && r == 0{
+                            return Err(Error::division_by_zero(location.clone(), String::new()));
+                        }
+
+                        let res = match opr {
+                            Operator::Plus => l.checked_add(r),
+                            Operator::Minus => l.checked_sub(r),
+                            Operator::Mul => l.checked_mul(r),
+                            Operator::Div => l.checked_div(r), // i64::MIN / -1 overflows
+                            _ => unreachable!("the comparisons and the logical operators are computed before")
+                        };
+                        res.map(Value::Int).ok_or_else(|| Error::arithmetic_overflow(location.clone(), String::new()))
+                    },
+
+                    // a string can only be concatenated with a string
+                    (Operator::Plus, Value::String(l), Value::String(r)) => Ok(Value::String(l + &r)),
+
+                    (opr, l, r) => {
+                        match (l.as_float(), r.as_float()) {
+                            (Some(l), Some(r)) => {
+                                match opr {
+                                    Operator::Plus => Ok(Value::Float(l + r)),
+                                    Operator::Minus => Ok(Value::Float(l - r)),
+                                    Operator::Mul => Ok(Value::Float(l * r)),
+                                    Operator::Div => {
+                                        if r == 0.0{
+                                            return Err(Error::division_by_zero(location.clone(), String::new()));
+                                        }
+                                        Ok(Value::Float(l / r))
+                                    },
+                                    _ => unreachable!("the comparisons and the logical operators are computed before")
+                                }
+                            },
+                            _ => {
+                                Err(Error::type_mismatch(
+                                    location.clone(), String::new(),
+                                    format!("The operator [{opr}] can't be used between {} and {}", l.ty(), r.ty())
+                                ))
+                            }
+                        }
+                    }
+                }
+            },
+
+            Expr::UnaryExpr { opr: UnaryOperator::Neg, value, .. } => {
+                match self.eval(value, location)? {
+                    Value::Int(val) => val.checked_neg().map(Value::Int).ok_or_else(|| Error::arithmetic_overflow(location.clone(), String::new())),
+                    Value::Float(val) => Ok(Value::Float(-val)),
+                    val => Err(Error::type_mismatch(location.clone(), String::new(), format!("A {} can't be negated", val.ty())))
+                }
+            },
+
+            Expr::UnaryExpr { opr: UnaryOperator::Not, value, .. } => {
+                self.eval_bool(value, location, "The operand of [not]").map(|val| Value::Bool(!val))
+            },
+
+            Expr::Block { .. } | Expr::Error => Err(Error::syntax_error(location.clone(), String::new()))
+        }
+    }
+
+    /// execute a single statement
+    pub fn execute(&mut self, statement: &Statement) -> Result<(), Error>{
+        self.execute_statement(statement).map(|_| ())
+    }
+
+    /// execute a single statement, returns what must be done after it
+    fn execute_statement(&mut self, statement: &Statement) -> Result<Flow, Error>{
+        match statement {
+            Statement::VarDeclaration { identifier, ty, value, location } => {
+                let value = self.eval(value, location)?;
+                if let Some(ty) = ty.filter(|ty| *ty != value.ty()){
+                    return Err(Error::type_mismatch(
+                        location.clone(), String::new(),
+                        format!("The variable [{identifier}] is a {ty}, it can't be set to a {}", value.ty())
+                    ));
+                }
+                let symbol = self.symbol(identifier, location)?;
+                self.variables.insert(symbol, value);
+            },
+
+            Statement::VarEdit { identifier, value, location } => {
+                let value = self.eval(value, location)?;
+
+                let symbol = self.symbol(identifier, location)?;
+                match self.variables.get_mut(&symbol) {
+                    Some(old) if old.ty() == value.ty() => *old = value,
+                    Some(old) => {
+                        return Err(Error::type_mismatch(
+                            location.clone(), String::new(),
+                            format!("The variable [{identifier}] is a {}, it can't be set to a {}", old.ty(), value.ty())
+                        ));
+                    },
+                    None => return Err(Error::undefined_variable(location.clone(), String::new(), identifier.clone()))
+                }
+            },
+
+            Statement::Print { value, location } => {
+                let value = self.eval(value, location)?;
+                self.print(&value);
+            },
+
+            Statement::FuncCall { identifier, args, location } => {
+                self.call(identifier, args, location)?;
+            },
+
+            Statement::FuncDeclaration { identifier, params, returns, body, .. } => self.declare_function(identifier, params, *returns, body),
+
+            Statement::Return { value, location } => {
+                let value = match value {
+                    Some(value) => Some(self.eval(value, location)?),
+                    None => None
+                };
+                return Ok(Flow::Return(value));
+            },
+
+            Statement::Expr { value, location } => {
+                self.eval(value, location)?;
+            },
+
+            Statement::If { condition, body, else_body, location } => {
+                if self.eval_bool(condition, location, "A condition")?{
+                    return self.execute_block(body, location);
+                }
+                else if let Some(else_body) = else_body{
+                    return self.execute_block(else_body, location);
+                }
+            },
+
+            Statement::While { condition, body, location } => {
+                while self.eval_bool(condition, location, "A condition")?{
+                    match self.execute_block(body, location)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Next | Flow::Continue => ()
+                    }
+                }
+            },
+
+            Statement::Break { .. } => return Ok(Flow::Break),
+
+            Statement::Continue { .. } => return Ok(Flow::Continue),
+
+            Statement::NoneOrError => ()
+        }
+
+        Ok(Flow::Next)
+    }
+
+    /// add a function, or replace the one which has the same name
+    fn declare_function(&mut self, identifier: &str, params: &[Param], returns: Option<Type>, body: &Expr){
+        self.functions.insert(identifier.into(), Rc::new(Function { params: params.to_vec(), returns, body: body.clone() }));
+    }
+
+    /// call a function, returns its value or None if it returns nothing
+    /// `location` is the location of the call, used by errors
+    pub fn call(&mut self, identifier: &str, args: &[Expr], location: &Location) -> Result<Option<Value>, Error>{
+        let function = self.functions.get(identifier).cloned()
+            .ok_or_else(|| Error::undefined_function(location.clone(), String::new(), identifier.into()))?;
+
+        if function.params.len() != args.len(){
+            return Err(Error::arity(location.clone(), String::new(), identifier.into(), function.params.len(), args.len()));
+        }
+        // the arguments are computed with the variables of the caller
+        let mut params = HashMap::new();
+        for (param, arg) in function.params.iter().zip(args){
+            let value = self.eval(arg, location)?;
+            if let Some(ty) = param.ty.filter(|ty| *ty != value.ty()){
+                return Err(Error::type_mismatch(
+                    location.clone(), String::new(),
+                    format!("The parameter [{}] of [{identifier}] is a {ty}, it can't be a {}", param.identifier, value.ty())
+                ));
+            }
+            params.insert(self.symbol(&param.identifier, &param.location)?, value);
+        }
+
+        let variables = std::mem::replace(&mut self.variables, params);
+        let res = self.execute_block(&function.body, location);
+        self.variables = variables; // restored even after an error, so the REPL can continue
+
+        match (res?, function.returns) {
+            (Flow::Return(Some(value)), Some(ty)) if value.ty() != ty => Err(Error::type_mismatch(
+                location.clone(), String::new(),
+                format!("The function [{identifier}] returns a {ty}, it can't return a {}", value.ty())
+            )),
+            (Flow::Return(value), _) => Ok(value),
+            _ => Ok(None)
+        }
+    }
+
+    /// compute an expression which must be a bool
+    /// `what` is the name of the expression in the error, like "A condition"
+    fn eval_bool(&mut self, expr: &Expr, location: &Location, what: &str) -> Result<bool, Error>{
+        match self.eval(expr, location)? {
+            Value::Bool(val) => Ok(val),
+            val => Err(Error::type_mismatch(location.clone(), String::new(), format!("{what} must be a bool, not a {}", val.ty())))
+        }
+    }
+
+    /// execute the statements of a Expr::Block
+    /// stops at a break or a continue, which is returned to the loop
+    fn execute_block(&mut self, block: &Expr, location: &Location) -> Result<Flow, Error>{
+        let code = match block {
+            Expr::Block { code } => code,
+            _ => return Err(Error::syntax_error(location.clone(), String::new()))
+        };
+
+        if self.depth >= MAX_DEPTH{
+            return Err(Error::stack_overflow(location.clone(), String::new()));
+        }
+
+        self.depth += 1;
+        let res = code.iter().map(|statement| self.execute_statement(statement)).find(|flow| flow != &Ok(Flow::Next));
+        self.depth -= 1; // even after an error, so the REPL can continue
+
+        res.unwrap_or(Ok(Flow::Next))
+    }
+
+    /// execute the statements, stops at the first error
+    /// the functions are declared first, so they can be called before their declaration
+    pub fn run(&mut self, statements: &[Statement]) -> CompilerResult<()>{
+        for statement in statements{
+            if let Statement::FuncDeclaration { identifier, params, returns, body, .. } = statement{
+                self.declare_function(identifier, params, *returns, body);
+            }
+        }
+
+        statements.iter().try_for_each(|statement| self.execute(statement)).map_err(|err| vec![err])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{program::{Program, with_stack}, resolver::Resolver};
+
+    /// resolve then run a program on a thread with the stack of a program, returns what it printed
+    /// the types are not checked, so the type errors are found by the interpreter
+    fn run(source: &str) -> CompilerResult<String>{
+        let statements = Program::new("test", source).parse()?;
+        let mut resolver = Resolver::new();
+        resolver.resolve(&statements)?;
+
+        with_stack(|| {
+            let mut interpreter = Interpreter::with_output(Vec::new());
+            interpreter.set_resolutions(resolver.resolutions().clone());
+            interpreter.run(&statements)?;
+            Ok(String::from_utf8(interpreter.output().clone()).unwrap())
+        }).map_err(|err| vec![err])?
+    }
+
+    #[test]
+    fn grammar(){
+        assert_eq!(run(include_str!("../grammar.txt")).unwrap(), "12\nHello world\n");
+    }
+
+    #[test]
+    fn expressions(){
+        assert_eq!(run("print (25 + 25 - 6 * 8 / 5)").unwrap(), "41\n");
+        assert_eq!(run("var x = 4\nvar y = (x - 2) * x / 4\nprint y").unwrap(), "2\n");
+        assert_eq!(run("var x = \"4\"\nprint \"x=\" + x + \"!\"").unwrap(), "x=4!\n");
+
+        // a string is only concatenated with a string
+        let errs = run("print 1 + 2 + \"3\"").unwrap_err();
+        assert!(errs[0].to_string().contains("The operator [+] can't be used between int and string"));
+
+        assert_eq!(run("print 0 - 1").unwrap(), "-1\n");
+        assert_eq!(run("var x = 3\nprint -x * 2 + -(1 - 8) / 2").unwrap(), "-3\n");
+        assert_eq!(run("print -1.5 - 1").unwrap(), "-2.5\n");
+
+        // a variable declared again can change its type
+        assert_eq!(run("var x = 1\nvar x = \"one\"\nprint x").unwrap(), "one\n");
+    }
+
+    #[test]
+    fn floats(){
+        assert_eq!(run("print 1.5 + 1").unwrap(), "2.5\n");
+        assert_eq!(run("print 1 / 3.0").unwrap(), "0.333333\n");
+        assert_eq!(run("var x = 2\nprint x * 0.5 - 1").unwrap(), "0\n");
+        assert_eq!(run("var x = 1.5\nx = 2.5\nprint x").unwrap(), "2.5\n");
+
+        assert!(run("print 1.5 / 0").is_err());
+        assert!(run("var x = 1.5\nx = 2").is_err());
+        assert!(run("print \"a\" * 1.5").is_err());
+        assert!(run("print \"x=\" + 0.25").is_err());
+    }
+
+    #[test]
+    fn float_format(){
+        // the excepted strings are the output of printf("%g")
+        assert_eq!(format_float(3.0), "3");
+        assert_eq!(format_float(-2.5), "-2.5");
+        assert_eq!(format_float(0.1 + 0.2), "0.3");
+        assert_eq!(format_float(100000.0), "100000");
+        assert_eq!(format_float(1234567.0), "1.23457e+06");
+        assert_eq!(format_float(0.0001), "0.0001");
+        assert_eq!(format_float(0.00001234), "1.234e-05");
+        assert_eq!(format_float(1e100), "1e+100");
+        assert_eq!(format_float(0.0), "0");
+    }
+
+    #[test]
+    fn comparisons(){
+        assert_eq!(run("print 1 < 2\nprint 2 <= 1\nprint 3 == 3\nprint 3 != 3\nprint 2 > 1.5\nprint 2 >= 2.0").unwrap(), "true\nfalse\ntrue\nfalse\ntrue\ntrue\n");
+        assert_eq!(run("print \"abc\" < \"abd\"\nprint \"a\" == \"a\"\nprint \"B\" > \"a\"").unwrap(), "true\ntrue\nfalse\n");
+
+        // the comparisons are done after the arithmetic
+        assert_eq!(run("var x = 3\nprint x + 1 == 2 * 2\nprint 1 < 2 == 2 < 3").unwrap(), "true\ntrue\n");
+        assert_eq!(run("var x = 5\nif x > 3 { print \"big\" } else { print \"small\" }").unwrap(), "big\n");
+
+        assert!(run("print 1 == \"1\"").is_err());
+        assert!(run("print \"a\" < 2.5").is_err());
+        assert!(run("print true < false").is_err());
+    }
+
+    #[test]
+    fn bools(){
+        assert_eq!(run("var t = true\nprint t\nprint not t\nprint t == false").unwrap(), "true\nfalse\nfalse\n");
+        assert!(run("print \"t=\" + true").is_err());
+        assert_eq!(run("print true and false\nprint true or false\nprint false or not true and true").unwrap(), "false\ntrue\nfalse\n");
+
+        // not takes the comparisons, and takes precedence over or
+        assert_eq!(run("print not 1 == 2\nprint not false and false\nprint true or true and false").unwrap(), "true\nfalse\ntrue\n");
+
+        // the right operand is only evaluated if it is needed, so the division by zero is never done
+        assert_eq!(run("var x = 0\nprint x != 0 and 10 / x > 1\nprint x == 0 or 10 / x > 1").unwrap(), "false\ntrue\n");
+        assert!(run("var x = 0\nprint x == 0 and 10 / x > 1").is_err());
+
+        assert!(run("print 1 and true").is_err());
+        assert!(run("print false or 1").is_err());
+        assert!(run("print not 1").is_err());
+        assert!(run("print -true").is_err());
+        assert!(run("print true + 1").is_err());
+        assert!(run("var b = true\nb = 1").is_err());
+    }
+
+    #[test]
+    fn conditions(){
+        let source = "var x = 2\nif x < 2 {\n    print \"a\"\n} else if x == 2 {\n    print \"b\"\n} else {\n    print \"c\"\n}";
+        assert_eq!(run(source).unwrap(), "b\n");
+        assert_eq!(run("if false { print 1 } else { print 2 }\nif true { print 3 }\nif false { print 4 }").unwrap(), "2\n3\n");
+
+        // a block can edit the variables around it, but its variables are removed at its end
+        let source = "var x = 1\nvar y = 1\nif x > 0 {\n    x = 2\n    var y = \"inner\"\n    var z = 3\n    print y\n}\nprint x\nprint y";
+        assert_eq!(run(source).unwrap(), "inner\n2\n1\n");
+        assert!(run("if true {\n    var z = 3\n}\nprint z").is_err());
+
+        // only a bool can be a condition
+        assert!(run("if 1 { print 1 }").is_err());
+        assert!(run("if \"a\" { print 1 }").is_err());
+        assert!(run("if 1.5 { print 1 }").is_err());
+    }
+
+    #[test]
+    fn loops(){
+        let source = "var i = 0\nvar sum = 0\nwhile i < 10 {\n    i = i + 1\n    if i == 3 { continue }\n    if i > 5 { break }\n    sum = sum + i\n}\nprint sum\nprint i";
+        assert_eq!(run(source).unwrap(), "12\n6\n");
+
+        // break leaves the innermost loop only
+        let source = "var i = 0\nwhile i < 3 {\n    var j = 0\n    while true {\n        j = j + 1\n        if j == 2 { break }\n    }\n    print i + j\n    i = i + 1\n}";
+        assert_eq!(run(source).unwrap(), "2\n3\n4\n");
+
+        assert_eq!(run("while false { print 1 }\nprint 2").unwrap(), "2\n");
+        assert!(run("while 1 { print 1 }").is_err());
+        assert!(run("var i = 0\nwhile i < 3 {\n    i = i + 1\n    var x = i / (2 - i)\n}").is_err());
+    }
+
+    #[test]
+    fn functions(){
+        let source = "fn fact(n) {\n    if n <= 1 { return 1 }\n    return n * fact(n - 1)\n}\nprint fact(10)\nprint twice(\"ab\")\nfn twice(s) {\n    return s + s\n}";
+        assert_eq!(run(source).unwrap(), "3628800\nabab\n");
+
+        // a function only sees its parameters and its variables
+        let source = "var x = 1\nfn f(x) {\n    x = x + 1\n    print x\n}\nf(5)\nprint x";
+        assert_eq!(run(source).unwrap(), "6\n1\n");
+        assert!(run("var x = 1\nfn f() { print x }\nf()").is_err());
+
+        // return leaves the loops of the function
+        let source = "fn first(n) {\n    var i = 0\n    while true {\n        i = i + 1\n        if i * i > n { return i }\n    }\n}\nprint first(50)";
+        assert_eq!(run(source).unwrap(), "8\n");
+
+        let errs = run("fn f(a, b) { }\nf(1)").unwrap_err();
+        assert!(errs[0].to_string().contains("ArityMismatch in test at 2:0"));
+        let errs = run("fn f() { return }\nprint f()").unwrap_err();
+        assert!(errs[0].to_string().contains("NoReturnValue in test at 2:6"));
+        let errs = run("fn f(n) { return f(n + 1) }\nprint f(0)").unwrap_err();
+        assert!(errs[0].to_string().contains("StackOverflow"));
+        let source = "fn f(n) {\n    while true {\n        if true {\n            if n >= 0 { return f(n + 1) + 1 }\n        }\n    }\n}\nprint f(0)";
+        assert!(run(source).unwrap_err()[0].to_string().contains("StackOverflow"));
+    }
+
+    #[test]
+    fn deep_recursion(){
+        // the programs run on a thread made by with_stack, so a recursion is only limited by MAX_DEPTH
+        // the body of the last call and the block of its if are the deepest blocks
+        let source = format!("fn f(n) {{\n    if n == 0 {{ return 0 }}\n    return 1 + f(n - 1)\n}}\nprint f(1000)\nprint f({})", MAX_DEPTH - 2);
+        assert_eq!(run(&source).unwrap(), format!("1000\n{}\n", MAX_DEPTH - 2));
+        assert!(run(&format!("fn f(n) {{\n    if n == 0 {{ return 0 }}\n    return 1 + f(n - 1)\n}}\nprint f({})", MAX_DEPTH - 1)).is_err());
+
+        // each call is in three blocks
+        let source = "fn f(n) {\n    while true {\n        if n > 0 { return 1 + f(n - 1) }\n        return 0\n    }\n}\nprint f(";
+        assert_eq!(run(&format!("{source}{})", MAX_DEPTH / 3 - 1)).unwrap(), format!("{}\n", MAX_DEPTH / 3 - 1));
+        assert!(run(&format!("{source}{})", MAX_DEPTH / 3)).is_err());
+    }
+
+    #[test]
+    fn annotations(){
+        assert_eq!(run("var x: int = 2\nfn f(s: string, n) -> int {\n    print s\n    return n * 2\n}\nprint f(\"a\", x)").unwrap(), "a\n4\n");
+
+        let errs = run("var x: string = 1").unwrap_err();
+        assert!(errs[0].to_string().contains("The variable [x] is a string, it can't be set to a int"));
+        assert!(run("fn f(n: float) { }\nf(1)").is_err());
+        assert!(run("fn f() -> int { return \"a\" }\nprint f()").is_err());
+    }
+
+    #[test]
+    fn runtime_errors(){
+        let errs = run("var x = 1\nprint y").unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("UndefinedVariable in test at 2:6"));
+
+        let errs = run("var x = 0\nvar y = 1\nprint y / x").unwrap_err();
+        assert!(errs[0].to_string().contains("DivisionByZero in test at 3:0"));
+
+        // an overflow is an error, like in the C backend
+        let errs = run("var x = 9223372036854775807\nprint x + 1").unwrap_err();
+        assert!(errs[0].to_string().contains("ArithmeticOverflow in test at 2:0"));
+        assert!(run("var x = -9223372036854775807 - 1\nprint x / -1").is_err());
+        assert!(run("var x = -9223372036854775807 - 1\nprint -x").is_err());
+        assert!(run("print 4294967296 * 4294967296").is_err());
+        assert!(run("print -\"a\"").is_err());
+
+        let errs = run("var x = \"a\" * 2").unwrap_err();
+        assert!(errs[0].to_string().contains("TypeMismatch in test at 1:0"));
+
+        assert!(run("var x = 1\nx = \"a\"").is_err());
+        assert!(run("x = 1").is_err());
+        assert!(run("f(1)").is_err());
+
+        // the execution stops at the first error
+        let statements = Program::new("test", "print 1\nprint x\nprint 2").parse().unwrap();
+        let mut interpreter = Interpreter::with_output(Vec::new());
+        let errs = interpreter.run(&statements).unwrap_err();
+        assert!(errs[0].to_string().contains("UndefinedVariable in test at 2:6"));
+        assert_eq!(interpreter.output(), b"1\n");
+    }
+}
@@ -1,49 +1,323 @@
-use errors::{PartialLocation, display_errors};
-use token::Tokenizer;
+use std::io::{Stdout, Write};
+
+use ast::{AbstractSyntaxTree, Statement};
+use driver::BuildOptions;
+use errors::{CompilerResult, PartialLocation, display_errors};
+use folder::ConstantFolder;
+use interpreter::Interpreter;
+use program::Program;
+use resolver::Resolver;
+use typechecker::TypeChecker;
+use token::{Token, Tokenizer};
 
 mod token;
 mod errors;
 mod ast;
+mod program;
+mod codegen;
+mod driver;
+mod interpreter;
+mod resolver;
+mod typechecker;
+mod folder;
 
-struct JIT{
-
+/// The REPL: each line of stdin is executed as soon as it is read.
+/// The variables are kept from one line to the next, and the value of an expression alone on its line is printed.
+#[allow(clippy::upper_case_acronyms)]
+struct JIT<W: Write = Stdout>{
+    interpreter: Interpreter<W>,
+    resolver: Resolver, // the variables and functions declared by the previous lines
+    checker: TypeChecker, // and their types
+    history: String // every line read, so the errors can show their line
 }
 
 impl JIT{
+    fn new() -> Self{
+        JIT { interpreter: Interpreter::new(), resolver: Resolver::new(), checker: TypeChecker::new(), history: String::new() }
+    }
+}
+
+impl<W: Write> JIT<W>{
+
+    /// tokenize, parse and execute a line
+    /// The names and the types are checked before the execution, then the statements before an error are executed and the variables they declare are kept.
+    fn run_line(&mut self, input: &str, line: u64) -> CompilerResult<()>{
+        self.history.push_str(input);
+        self.history.push('\n');
+
+        let mut tokenizer = Tokenizer::new(input, PartialLocation::stdin(line));
+        tokenizer.tokenize();
+        let tokens = tokenizer.result()?;
+
+        let mut parser = AbstractSyntaxTree::new(&tokens, PartialLocation::stdin(line), input);
+        parser.build_tree();
+        let statements = parser.result()?;
+
+        // a line with an error declares nothing, even if the error is found when it runs
+        let (mut resolver, mut checker) = (self.resolver.clone(), self.checker.clone());
+        program::check(&mut resolver, &mut checker, &statements).map_err(|errs| {
+            errs.into_iter().map(|err| err.source_line(&self.history, 1)).collect::<Vec<_>>()
+        })?;
+        self.interpreter.set_resolutions(resolver.resolutions().clone());
 
-    /// run the Tokenizer
+        let statements = ConstantFolder::new().fold(&statements).map_err(|errs| {
+            errs.into_iter().map(|err| err.source_line(&self.history, 1)).collect::<Vec<_>>()
+        })?;
+
+        statements.iter().try_for_each(|statement| match statement {
+            Statement::Expr { value, location } => {
+                self.interpreter.eval(value, location).map(|value| self.interpreter.print(&value))
+            },
+            // like an expression, the value returned by a call alone on its line is printed
+            Statement::FuncCall { identifier, args, location } => {
+                self.interpreter.call(identifier, args, location).map(|value| {
+                    if let Some(value) = value{
+                        self.interpreter.print(&value)
+                    }
+                })
+            },
+            _ => self.interpreter.execute(statement)
+        }).map_err(|err| vec![err.source_line(&self.history, 1)])?;
+
+        (self.resolver, self.checker) = (resolver, checker);
+        Ok(())
+    }
+
+    /// run the Tokenizer, the parser and the interpreter on each line of stdin
+    /// The lines of a block are read until it is closed, then they are executed together.
     pub fn run_stdio(&mut self){
-        let mut line = 0;
+        let mut line = 1;
         loop {
-            line += 1;
             let mut input = String::new();
-            match std::io::stdin().read_line(&mut input) {
-                Ok(_) => (),
-                Err(err) => {
-                    eprintln!("error while reading stdin: {err}");
-                    break
-                }
-            };
-            input = input.trim_end().to_string();
-            
-            let mut tokenizer = Tokenizer::new(&input, PartialLocation::stdin(line));
-            tokenizer.tokenize();
-            let res = tokenizer.result();
-            match res {
-                Ok(tokens) => {
-                    println!("{:?}", tokens);
+            let mut lines = 0;
 
-                }
-                Err(errs) => {
-                    display_errors(errs);
+            loop {
+                match std::io::stdin().read_line(&mut input) {
+                    Ok(0) if input.is_empty() => return, // end of stdin
+                    Ok(0) => break, // end of stdin in a block, the error is reported by the parser
+                    Ok(_) => lines += 1,
+                    Err(err) => {
+                        eprintln!("error while reading stdin: {err}");
+                        return
+                    }
+                };
+
+                if !is_incomplete(&input){
+                    break;
                 }
             }
 
+            if let Err(errs) = self.run_line(input.trim_end(), line){
+                display_errors(errs);
+            }
+            line += lines;
+        }
+    }
+}
+
+/// check if some blocks are not closed yet, then the REPL has to read the next lines
+fn is_incomplete(input: &str) -> bool{
+    let mut tokenizer = Tokenizer::new(input, PartialLocation::stdin(0));
+    tokenizer.tokenize();
+
+    match tokenizer.result() {
+        Ok(tokens) => {
+            let opened = tokens.iter().filter(|tk| tk.token == Token::OpeningBrace).count();
+            let closed = tokens.iter().filter(|tk| tk.token == Token::ClosingBrace).count();
+            opened > closed
+        },
+        Err(_) => false // the errors are reported right away
+    }
+}
+
+/// read a whole file, exit if it can't be read
+fn read_program(path: &str) -> Program{
+    match Program::from_file(path) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("error while reading {path}: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// print the result of a compilation, exit if there are errors
+fn print_result<T>(result: CompilerResult<T>, print: impl FnOnce(T)){
+    match result {
+        Ok(value) => print(value),
+        Err(errs) => {
+            display_errors(errs);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn usage(){
+    eprintln!("usage:");
+    eprintln!("  language                          read code from stdin");
+    eprintln!("  language run <file>               execute a file");
+    eprintln!("  language parse <file>             print the statements of a file");
+    eprintln!("  language c <file>                 print the C translation of a file");
+    eprintln!("  language build <file> [options]   compile a file to an executable");
+    eprintln!();
+    eprintln!("build options:");
+    eprintln!("  -o <path>        path of the executable, the name of the file without extension by default");
+    eprintln!("  -O<level>        optimisation level, from 0 to 3");
+    eprintln!("  --keep-c         keep the intermediate C file (<path>.c), which must not exist");
+    eprintln!("  --cc <compiler>  C compiler to use, $CC or the first of cc, gcc, tcc, clang by default");
+}
+
+/// read the options of the build command
+/// returns None if an option is invalid
+fn build_options(path: &str, args: &[String]) -> Option<BuildOptions>{
+    // a file without extension gives `<file>.out`, so the executable is not written over it
+    let source = std::path::Path::new(path);
+    let default_output = if source.extension().is_some() { source.with_extension("") } else { source.with_extension("out") };
+    let mut options = BuildOptions::new(default_output);
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next(){
+        match arg.as_str() {
+            "-o" => options = options.output(args.next()?),
+            "--keep-c" => options = options.keep_c(true),
+            "--cc" => options = options.compiler(args.next()?.as_str()),
+            _ => {
+                let level = arg.strip_prefix("-O")?.parse().ok()?;
+                options = options.optimisation(level);
+            }
+        }
+    }
+
+    Some(options)
+}
+
+/// run the command given by the arguments
+fn run_command(args: &[String]){
+    match (args.get(1).map(String::as_str), args.get(2)) {
+        (None, _) => {
+            let mut intepreter = JIT::new();
+            intepreter.run_stdio();
+        },
+        (Some("run"), Some(path)) => {
+            print_result(read_program(path).run(), |_| ());
+        },
+        (Some("parse"), Some(path)) => {
+            print_result(read_program(path).parse(), |statements| println!("{:#?}", statements));
+        },
+        (Some("c"), Some(path)) => {
+            print_result(read_program(path).to_c(), |code| print!("{code}"));
+        },
+        (Some("build"), Some(path)) => {
+            let Some(options) = build_options(path, &args[3..]) else {
+                usage();
+                std::process::exit(1);
+            };
+            print_result(driver::build(&read_program(path), &options), |executable| println!("{}", executable.display()));
+        },
+        _ => {
+            usage();
+            std::process::exit(1);
         }
     }
 }
 
 fn main(){
-    let mut intepreter = JIT{};
-    intepreter.run_stdio();
-}
\ No newline at end of file
+    let args: Vec<String> = std::env::args().collect();
+
+    // the whole command runs on one thread with a big stack, the REPL included
+    if let Err(err) = program::with_stack(|| run_command(&args)){
+        display_errors(vec![err]);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// run some lines in the same REPL, returns what was printed
+    fn repl(lines: &[&str]) -> (String, Vec<CompilerResult<()>>){
+        let mut jit = JIT { interpreter: Interpreter::with_output(Vec::new()), resolver: Resolver::new(), checker: TypeChecker::new(), history: String::new() };
+        let res = lines.iter().enumerate().map(|(i, line)| jit.run_line(line, i as u64 + 1)).collect();
+        (String::from_utf8(jit.interpreter.output().clone()).unwrap(), res)
+    }
+
+    #[test]
+    fn state_is_kept(){
+        let (out, res) = repl(&["var x = 25", "print x", "x = x + 1", "x", "x * 2"]);
+        assert!(res.iter().all(|res| res.is_ok()));
+        assert_eq!(out, "25\n26\n52\n");
+    }
+
+    #[test]
+    fn continue_after_errors(){
+        let (out, res) = repl(&["var x = 25", "var y = x / 0", "print (x", "print y", "x"]);
+        assert!(res[1].is_err());
+        assert!(res[2].is_err());
+        assert!(res[4].is_ok());
+        assert_eq!(out, "25\n");
+
+        // the error shows the line where it happened
+        let err = &res[3].as_ref().unwrap_err()[0];
+        assert!(err.to_string().contains("in stdin at 4:6\n|\tprint y"));
+        assert!(err.to_string().contains("UndefinedVariable"));
+
+        // a variable whose value fails is not declared for the next lines
+        let (out, res) = repl(&["var z = 0", "var a = 1 / z", "print a", "a = 5", "var a = 2", "print a"]);
+        assert!(res[1].as_ref().unwrap_err()[0].to_string().contains("DivisionByZero"));
+        assert!(res[2].as_ref().unwrap_err()[0].to_string().contains("The variable [a] is not declared"));
+        assert!(res[3].as_ref().unwrap_err()[0].to_string().contains("Can't assign [a], it is not declared"));
+        assert!(res[4].is_ok() && res[5].is_ok());
+        assert_eq!(out, "2\n");
+    }
+
+    #[test]
+    fn blocks(){
+        assert!(is_incomplete("if x {\n"));
+        assert!(is_incomplete("if x {\n    if y { print 1 }\n"));
+        assert!(!is_incomplete("if x {\n    print 1\n}\n"));
+        assert!(!is_incomplete("print \"{\"\n"));
+
+        // a block read on several lines is run at once
+        let (out, res) = repl(&["var x = 1", "if x == 1 {\n    var y = 2\n    print y\n} else {\n    print 0\n}", "print x"]);
+        assert!(res.iter().all(|res| res.is_ok()));
+        assert_eq!(out, "2\n1\n");
+    }
+
+    #[test]
+    fn checked_before_execution(){
+        // nothing is executed when a name is not declared
+        let (out, res) = repl(&["var y = 2 + z", "print y", "z = 3", "print 1\nprint y"]);
+        assert!(res.iter().all(|res| res.is_err()));
+        assert_eq!(out, "");
+
+        let err = &res[2].as_ref().unwrap_err()[0];
+        assert!(err.to_string().contains("in stdin at 3:0\n|\tz = 3"));
+
+        // the types are kept from one line to the next
+        let (out, res) = repl(&["var s = \"a\"", "print s - 1", "s = 2", "s + \"2\""]);
+        assert!(res[1].is_err());
+        assert!(res[2].is_err());
+        assert!(res[3].is_ok());
+        assert_eq!(out, "a2\n");
+    }
+
+    #[test]
+    fn functions(){
+        // the value returned by a call alone on its line is printed
+        let (out, res) = repl(&["fn sq(x) {\n    return x * x\n}", "sq(4)", "fn hi() { print \"hi\" }", "hi()", "sq(1, 2)"]);
+        assert!(res[..4].iter().all(|res| res.is_ok()));
+        assert!(res[4].is_err());
+        assert_eq!(out, "16\nhi\n");
+    }
+
+    #[test]
+    fn build_output(){
+        assert_eq!(build_options("dir/prog.lang", &[]), Some(BuildOptions::new("dir/prog")));
+        assert_eq!(build_options("prog.lang", &["-o".into(), "exe".into(), "-O2".into()]), Some(BuildOptions::new("exe").optimisation(2)));
+
+        // a source without extension is not the default executable
+        assert_eq!(build_options("prog", &[]), Some(BuildOptions::new("prog.out")));
+        assert_eq!(build_options("prog", &["-x".into()]), None);
+    }
+}
@@ -0,0 +1,223 @@
+#![allow(dead_code)]
+
+use std::{path::Path, thread};
+
+use crate::{token::Tokenizer, folder::ConstantFolder, ast::{AbstractSyntaxTree, Statement}, codegen::CodeGen, interpreter::Interpreter, resolver::{Resolutions, Resolver}, typechecker::{TypeChecker, Types}, errors::{CompilerResult, Error, Location, PartialLocation}};
+
+
+/// A whole source file.
+/// Unlike the REPL, the file is tokenized and parsed at once, so a line can use what was declared before it.
+pub struct Program{
+    filename: String,
+    source: String
+}
+
+impl Program{
+    /// create a program from its source code
+    /// `filename` is only used by errors
+    pub fn new(filename: impl Into<String>, source: impl Into<String>) -> Self{
+        Program {
+            filename: filename.into(),
+            source: source.into()
+        }
+    }
+
+    /// read a program from a file
+    pub fn from_file(path: impl AsRef<Path>) -> std::io::Result<Self>{
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+        Ok(Self::new(path.display().to_string(), source))
+    }
+
+    pub fn filename(&self) -> &str{
+        &self.filename
+    }
+
+    pub fn source(&self) -> &str{
+        &self.source
+    }
+
+    /// tokenize then parse the whole file
+    /// returns a statement for each line which is not empty
+    pub fn parse(&self) -> CompilerResult<Vec<Statement>>{
+        let mut tokenizer = Tokenizer::new(&self.source, PartialLocation::new(self.filename.as_str(), 1));
+        tokenizer.tokenize();
+        let tokens = tokenizer.result()?;
+
+        let mut parser = AbstractSyntaxTree::new(&tokens, PartialLocation::new(self.filename.as_str(), 1), &self.source);
+        parser.build_tree();
+        parser.result()
+    }
+
+    /// parse the whole file then check that every name is declared and find the types
+    /// returns the statements, the declaration used by each name and the types
+    pub fn check(&self) -> CompilerResult<(Vec<Statement>, Resolutions, Types)>{
+        let statements = self.parse()?;
+
+        let mut resolver = Resolver::new();
+        let types = check(&mut resolver, &mut TypeChecker::new(), &statements).map_err(|errs| {
+            errs.into_iter().map(|err| err.source_line(&self.source, 1)).collect::<Vec<_>>()
+        })?;
+        Ok((statements, resolver.resolutions().clone(), types))
+    }
+
+    /// check the whole file then compute its constant expressions
+    pub fn optimize(&self) -> CompilerResult<(Vec<Statement>, Resolutions, Types)>{
+        let (statements, resolutions, types) = self.check()?;
+
+        let statements = ConstantFolder::new().fold(&statements).map_err(|errs| {
+            errs.into_iter().map(|err| err.source_line(&self.source, 1)).collect::<Vec<_>>()
+        })?;
+        Ok((statements, resolutions, types))
+    }
+
+    /// execute the whole file with the interpreter
+    pub fn run(&self) -> CompilerResult<()>{
+        let (statements, resolutions, _) = self.optimize()?;
+
+        let mut interpreter = Interpreter::new();
+        interpreter.set_resolutions(resolutions);
+        interpreter.run(&statements).map_err(|errs| {
+            errs.into_iter().map(|err| err.source_line(&self.source, 1)).collect()
+        })
+    }
+
+    /// translate the whole file to C
+    pub fn to_c(&self) -> CompilerResult<String>{
+        let (statements, resolutions, types) = self.optimize()?;
+        Ok(CodeGen::new().generate(&statements, &resolutions, &types))
+    }
+}
+
+/// the size of the stack of the thread made by with_stack
+/// It is enough for interpreter::MAX_DEPTH calls and for expressions of several thousands of operators in a debug build.
+/// The memory is only used when it is needed.
+pub const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+/// run `f` on a thread with a stack of STACK_SIZE
+/// Each pass goes through the statements and the expressions recursively, so the whole compilation and the execution run on this thread.
+/// returns an error if the thread can't be created
+pub fn with_stack<T: Send>(f: impl FnOnce() -> T + Send) -> Result<T, Error>{
+    thread::scope(|scope| {
+        let thread = thread::Builder::new().stack_size(STACK_SIZE).spawn_scoped(scope, f).map_err(|err| {
+            Error::thread(Location::new("not specified", 0, 0), String::new(), format!("The thread of the compiler can't be created: {err}"))
+        })?;
+
+        Ok(thread.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+    })
+}
+
+/// run the Resolver then the TypeChecker with the symbols it found
+/// the errors of both are returned, sorted by location
+pub fn check(resolver: &mut Resolver, checker: &mut TypeChecker, statements: &[Statement]) -> CompilerResult<Types>{
+    let resolved = resolver.resolve(statements);
+    match (resolved, checker.check(statements, resolver.resolutions())) {
+        (Ok(()), Ok(types)) => Ok(types),
+        (resolved, checked) => {
+            let mut errs = resolved.err().unwrap_or_default();
+            errs.extend(checked.err().unwrap_or_default());
+            errs.sort();
+            Err(errs)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ast::Expr, errors::Location};
+
+    #[test]
+    fn grammar(){
+        let program = Program::new("grammar.txt", include_str!("../grammar.txt"));
+        let statements = program.parse().unwrap();
+
+        let loc = |line| Location::new("grammar.txt", line, 0);
+
+        assert_eq!(statements, vec![
+            Statement::VarDeclaration { identifier: "x".into(), ty: None, value: Expr::IntLitteral(25), location: loc(1) },
+            Statement::VarEdit { identifier: "x".into(), value: Expr::IntLitteral(12), location: loc(2) },
+            Statement::VarDeclaration { identifier: "other".into(), ty: None, value: Expr::StringLitteral("Hello world".into()), location: loc(3) },
+            Statement::Print { value: Expr::Identifier { identifier: "x".into(), location: Location::new("grammar.txt", 4, 6) }, location: loc(4) },
+            Statement::Print { value: Expr::Identifier { identifier: "other".into(), location: Location::new("grammar.txt", 5, 6) }, location: loc(5) },
+        ]);
+    }
+
+    #[test]
+    fn error_location(){
+        // tokenizer error
+        let program = Program::new("file.txt", "var x = 1\n\nprint \"hello");
+        let errs = program.parse().unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("print \"hello"));
+
+        // parser error
+        let program = Program::new("file.txt", "var x = 1\nprint x\nx = (2 + 3");
+        let errs = program.parse().unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("in file.txt at 3:"));
+    }
+
+    #[test]
+    fn comments(){
+        let program = Program::new("file.txt", "// a comment\nvar x = 1 /* on\ntwo lines */ + 2\nprint x // the end");
+        let statements = program.parse().unwrap();
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[1].location(), Some(&Location::new("file.txt", 4, 0)));
+
+        // the errors after a comment show the right line
+        let program = Program::new("file.txt", "/*\n*/ var x = 1 +\nprint x");
+        let errs = program.parse().unwrap_err();
+        assert!(errs[0].to_string().contains("in file.txt at 2:"));
+        assert!(errs[0].to_string().contains("|\t*/ var x = 1 +"));
+    }
+
+    #[test]
+    fn to_c(){
+        let program = Program::new("file.txt", "var x = 1\nprint x + 1\nprint y");
+        let errs = program.to_c().unwrap_err();
+        assert_eq!(errs.len(), 1);
+
+        // the error shows the line of the problem
+        assert!(errs[0].to_string().contains("in file.txt at 3:6\n|\tprint y"));
+    }
+
+    #[test]
+    fn check(){
+        // the names are checked before the execution, so nothing is printed
+        let program = Program::new("file.txt", "print 1
+if true {
+    var x = 1
+}
+x = 2");
+        let errs = program.run().unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("in file.txt at 5:0\n|\tx = 2"));
+
+        assert!(Program::new("file.txt", "fn f(a, a) { }").check().is_err());
+    }
+
+    #[test]
+    fn long_expressions(){
+        // every pass goes through the expressions recursively, it has the stack of with_stack
+        let program = Program::new("file.txt", format!("var x = 1\nprint x{}", " + x".repeat(3000)));
+        let c = with_stack(|| program.to_c()).unwrap().unwrap();
+        assert_eq!(c.matches("lang_add(").count(), 3000 + 1); // the calls and the definition of the runtime
+        assert!(with_stack(|| program.check()).unwrap().is_ok());
+    }
+
+    #[test]
+    fn optimize(){
+        let program = Program::new("file.txt", "var x = (25 + 25 - 6 * 8 / 5)\nprint x");
+        let (statements, _, _) = program.optimize().unwrap();
+        assert_eq!(statements[0], Statement::VarDeclaration { identifier: "x".into(), ty: None, value: Expr::IntLitteral(41), location: Location::new("file.txt", 1, 0) });
+        assert!(program.to_c().unwrap().contains("long long v_x = 41LL;"));
+
+        // the errors of the constant expressions are found before the execution
+        let program = Program::new("file.txt", "print 1\nprint 2 / (1 - 1)");
+        let errs = program.run().unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("DivisionByZero in file.txt at 2:0\n|\tprint 2 / (1 - 1)"));
+        assert!(program.to_c().is_err());
+    }
+}
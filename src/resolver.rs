@@ -0,0 +1,340 @@
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{ast::{Expr, Param, Statement}, errors::{CompilerResult, Error, Location}};
+
+
+/// a variable declared by the program
+/// each declaration is a different symbol, even if it has the name of a previous one
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Symbol(usize);
+
+/// The symbol declared or used by each name of the program, found by the Resolver.
+/// A name is found by its location: the location of a var declaration, of a parameter, of a variable in an expression or of an assignment.
+/// The passes after the Resolver use it instead of looking for the names in the blocks again.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Resolutions{
+    symbols: HashMap<Location, Symbol>
+}
+
+impl Resolutions{
+    /// the symbol declared or used at a location, None if the name is not declared
+    pub fn symbol(&self, location: &Location) -> Option<Symbol>{
+        self.symbols.get(location).copied()
+    }
+}
+
+
+/// Check that every name used by the statements is declared, before they are executed or translated, and find which declaration it uses.
+/// The scopes follow the blocks:
+/// - a variable can be used after its declaration until the end of its block
+/// - `var` in an inner block shadows the variable of the outer blocks until the end of the inner block
+/// - `var` in the same block declares the variable again, like the previous declaration was a different variable
+/// - the value of `var` is computed before the declaration, so `var x = x + 1` uses the previous `x`
+/// - a function only sees its parameters and its own variables, its body is a block inside its parameters
+///
+/// Each function can be called before its declaration, and it can only be declared once in the statements resolved together.
+/// The resolver keeps its scopes and its resolutions, so the REPL can check each line with the variables of the previous ones.
+#[derive(Clone)]
+pub struct Resolver{
+    scopes: Vec<HashMap<String, Symbol>>, // the variables declared in each block, the innermost is the last
+    functions: HashMap<String, usize>, // the number of parameters of each function
+    declarations: usize, // the number of symbols declared
+    resolutions: Resolutions,
+    err: Vec<Error>
+}
+
+impl Resolver{
+    pub fn new() -> Self{
+        Resolver {
+            scopes: vec![HashMap::new()],
+            functions: HashMap::new(),
+            declarations: 0,
+            resolutions: Resolutions::default(),
+            err: Vec::new()
+        }
+    }
+
+    /// the symbols found for every statement resolved so far
+    pub fn resolutions(&self) -> &Resolutions{
+        &self.resolutions
+    }
+
+    /// the symbol of a variable, from the innermost block which declares it
+    fn lookup(&self, identifier: &str) -> Option<Symbol>{
+        self.scopes.iter().rev().find_map(|scope| scope.get(identifier)).copied()
+    }
+
+    /// check if a variable is declared in the current block or in the blocks around it
+    pub fn is_declared(&self, identifier: &str) -> bool{
+        self.lookup(identifier).is_some()
+    }
+
+    /// a new symbol declared at `location`
+    fn new_symbol(&mut self, location: &Location) -> Symbol{
+        let symbol = Symbol(self.declarations);
+        self.declarations += 1;
+        self.resolutions.symbols.insert(location.clone(), symbol);
+        symbol
+    }
+
+    /// declare a variable in the innermost block
+    fn declare(&mut self, identifier: &str, location: &Location){
+        let symbol = self.new_symbol(location);
+        self.scopes.last_mut().unwrap().insert(identifier.into(), symbol); // there is always a scope
+    }
+
+    /// record the symbol used by the name at `location`
+    /// returns false if the name is not declared
+    fn use_variable(&mut self, identifier: &str, location: &Location) -> bool{
+        match self.lookup(identifier) {
+            Some(symbol) => {
+                self.resolutions.symbols.insert(location.clone(), symbol);
+                true
+            },
+            None => false
+        }
+    }
+
+    /// check the names used by an expression
+    fn expr(&mut self, expr: &Expr){
+        match expr {
+            Expr::Identifier { identifier, location } => {
+                if !self.use_variable(identifier, location){
+                    self.err.push(Error::undefined_variable(location.clone(), String::new(), identifier.clone()));
+                }
+            },
+            Expr::Call { identifier, args, location } => self.call(identifier, args, location),
+            Expr::BinaryExpr { l, r, .. } => {
+                self.expr(l);
+                self.expr(r);
+            },
+            Expr::UnaryExpr { value, .. } => self.expr(value),
+            Expr::Block { .. } => self.block(expr),
+            Expr::IntLitteral(_) | Expr::FloatLitteral(_) | Expr::StringLitteral(_) | Expr::BoolLitteral(_) | Expr::Error => ()
+        }
+    }
+
+    /// check that a function exists and gets the right number of arguments
+    fn call(&mut self, identifier: &str, args: &[Expr], location: &Location){
+        match self.functions.get(identifier) {
+            Some(&params) if params != args.len() => {
+                self.err.push(Error::arity(location.clone(), String::new(), identifier.into(), params, args.len()));
+            },
+            Some(_) => (),
+            None => self.err.push(Error::undefined_function(location.clone(), String::new(), identifier.into()))
+        }
+
+        for arg in args{
+            self.expr(arg);
+        }
+    }
+
+    /// check the statements of a Expr::Block in a new scope
+    fn block(&mut self, block: &Expr){
+        if let Expr::Block { code } = block{
+            self.scopes.push(HashMap::new());
+            for statement in code{
+                self.statement(statement);
+            }
+            self.scopes.pop();
+        }
+    }
+
+    /// check the body of a function, which only sees the parameters
+    fn function(&mut self, params: &[Param], body: &Expr){
+        let scopes = std::mem::replace(&mut self.scopes, vec![HashMap::new()]);
+
+        let mut declared = HashSet::new();
+        for param in params{
+            if !declared.insert(param.identifier.as_str()){
+                self.err.push(Error::duplicate_parameter(param.location.clone(), String::new(), param.identifier.clone()));
+            }
+            self.declare(&param.identifier, &param.location);
+        }
+
+        self.block(body);
+        self.scopes = scopes;
+    }
+
+    fn statement(&mut self, statement: &Statement){
+        match statement {
+            Statement::VarDeclaration { identifier, value, location, .. } => {
+                self.expr(value);
+                self.declare(identifier, location);
+            },
+
+            Statement::VarEdit { identifier, value, location } => {
+                if !self.use_variable(identifier, location){
+                    self.err.push(Error::undefined_assignment(location.clone(), String::new(), identifier.clone()));
+                }
+                self.expr(value);
+            },
+
+            Statement::Print { value, .. } | Statement::Expr { value, .. } => self.expr(value),
+
+            Statement::FuncCall { identifier, args, location } => self.call(identifier, args, location),
+
+            Statement::FuncDeclaration { params, body, .. } => self.function(params, body),
+
+            Statement::Return { value, .. } => {
+                if let Some(value) = value{
+                    self.expr(value);
+                }
+            },
+
+            Statement::If { condition, body, else_body, .. } => {
+                self.expr(condition);
+                self.block(body);
+                if let Some(else_body) = else_body{
+                    self.block(else_body);
+                }
+            },
+
+            Statement::While { condition, body, .. } => {
+                self.expr(condition);
+                self.block(body);
+            },
+
+            Statement::Break { .. } | Statement::Continue { .. } | Statement::NoneOrError => ()
+        }
+    }
+
+    /// check the statements, the functions are declared first
+    /// the errors don't have their line of code, the caller knows the source
+    pub fn resolve(&mut self, statements: &[Statement]) -> CompilerResult<()>{
+        // a function of the previous statements can be declared again, like in the lines of the REPL
+        let mut declared = HashSet::new();
+        for statement in statements{
+            if let Statement::FuncDeclaration { identifier, params, location, .. } = statement{
+                if !declared.insert(identifier){
+                    self.err.push(Error::duplicate_function(location.clone(), String::new(), identifier.clone()));
+                }
+                self.functions.insert(identifier.clone(), params.len());
+            }
+        }
+
+        for statement in statements{
+            self.statement(statement);
+        }
+
+        if self.err.is_empty(){
+            Ok(())
+        }
+        else {
+            Err(std::mem::take(&mut self.err))
+        }
+    }
+}
+
+impl Default for Resolver{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::Program;
+
+    /// parse then resolve a program
+    fn resolve(source: &str) -> CompilerResult<()>{
+        let statements = Program::new("test", source).parse()?;
+        Resolver::new().resolve(&statements)
+    }
+
+    #[test]
+    fn variables(){
+        assert!(resolve(include_str!("../grammar.txt")).is_ok());
+        assert!(resolve("var x = 1\nx = x + 1\nprint x").is_ok());
+
+        let errs = resolve("var x = 1\nprint x + y").unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("UndefinedVariable in test at 2:10"));
+
+        let errs = resolve("y = 1").unwrap_err();
+        assert!(errs[0].to_string().contains("Can't assign [y], it is not declared"));
+
+        // a variable can't be used in its own declaration
+        assert!(resolve("var x = x").is_err());
+
+        // every statement is checked
+        assert_eq!(resolve("print a\nb = 1\nprint c + d").unwrap_err().len(), 4);
+    }
+
+    #[test]
+    fn blocks(){
+        assert!(resolve("var x = 1\nif true {\n    var x = x + 1\n    print x\n}\nprint x").is_ok());
+        assert!(resolve("var i = 0\nwhile i < 3 {\n    var j = i\n    i = j + 1\n}").is_ok());
+
+        // the variables of a block can't be used after it
+        let errs = resolve("if true {\n    var y = 1\n} else {\n    print y\n}\nprint y\ny = 2").unwrap_err();
+        assert_eq!(errs.len(), 3);
+        assert!(errs[0].to_string().contains("in test at 4:10"));
+
+        assert!(resolve("while true {\n    var y = 1\n}\nprint y").is_err());
+    }
+
+    #[test]
+    fn functions(){
+        assert!(resolve("print f(1)\nfn f(a) {\n    var b = a\n    return g(b)\n}\nfn g(b) { return b }").is_ok());
+
+        // a function does not see the variables of the program
+        let errs = resolve("var x = 1\nfn f(a) {\n    return a + x\n}").unwrap_err();
+        assert!(errs[0].to_string().contains("UndefinedVariable in test at 3:15"));
+        assert!(resolve("fn f(a) {\n    var b = 1\n}\nprint b").is_err());
+
+        let errs = resolve("fn f(a, b, a) { }").unwrap_err();
+        assert!(errs[0].to_string().contains("DuplicateParameter in test at 1:11"));
+
+        let errs = resolve("fn f() { print 1 }\nfn f(a) { return a }\nprint f(1)").unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("DuplicateFunction in test at 2:0"));
+
+        // the functions of the previous lines of the REPL can be declared again
+        let mut resolver = Resolver::new();
+        resolver.resolve(&Program::new("test", "fn f() { print 1 }").parse().unwrap()).unwrap();
+        assert!(resolver.resolve(&Program::new("test", "fn f(a) { return a }\nprint f(1)").parse().unwrap()).is_ok());
+
+        let errs = resolve("fn f(a) { }\nf()\nprint g()").unwrap_err();
+        assert_eq!(errs.len(), 2);
+        assert!(errs[0].to_string().contains("ArityMismatch in test at 2:0"));
+        assert!(errs[1].to_string().contains("UndefinedFunction in test at 3:6"));
+    }
+
+    #[test]
+    fn state_is_kept(){
+        // like the REPL, the statements can be checked in several parts
+        let mut resolver = Resolver::new();
+        let parse = |source| Program::new("test", source).parse().unwrap();
+
+        assert!(resolver.resolve(&parse("var x = 1\nfn f() { }")).is_ok());
+        assert!(resolver.resolve(&parse("print x\nf()")).is_ok());
+        assert!(resolver.resolve(&parse("print y")).is_err());
+        assert!(resolver.resolve(&parse("print x")).is_ok());
+    }
+
+    #[test]
+    fn resolutions(){
+        // each name gets the symbol of the declaration it uses, found by the location of the name
+        let statements = Program::new("test", "var x = 1\nif true {\n    var x = x + 1\n    print x\n}\nx = x\nfn f(x) { return x }").parse().unwrap();
+        let mut resolver = Resolver::new();
+        assert!(resolver.resolve(&statements).is_ok());
+        let symbol = |line, char_pos| resolver.resolutions().symbol(&Location::new("test", line, char_pos));
+
+        let (outer, inner, param) = (symbol(1, 0), symbol(3, 4), symbol(7, 5));
+        assert!(outer.is_some() && inner.is_some() && param.is_some());
+        assert!(outer != inner && outer != param);
+        assert_eq!(symbol(3, 12), outer);
+        assert_eq!(symbol(4, 10), inner);
+        assert_eq!((symbol(6, 0), symbol(6, 4)), (outer, outer));
+        assert_eq!(symbol(7, 17), param);
+
+        // a name which is not declared has no symbol
+        let statements = Program::new("test", "print y").parse().unwrap();
+        assert!(resolver.resolve(&statements).is_err());
+        assert_eq!(resolver.resolutions().symbol(&Location::new("test", 1, 6)), None);
+    }
+}
@@ -1,12 +1,16 @@
 #![allow(dead_code)]
 
 
-use std::str::Chars;
+use std::fmt::Display;
+use std::str::{Chars, Lines};
 use std::iter::Peekable;
 
 use crate::errors::{Error, Location, CompilerResult, PartialLocation};
 
 
+/// the biggest litteral integer, it is the magnitude of i64::MIN: `-9223372036854775808` is a Minus followed by it
+pub const MAX_INT_MAGNITUDE: u64 = i64::MIN.unsigned_abs();
+
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
@@ -23,10 +27,31 @@ pub enum Token{
     ///operator a/b
     Div,
 
-    /// represent all literal integer
-    /// Note: currently the compiler only supports integer
+    ///operator a==b
+    Equal,
+
+    ///operator a!=b
+    NotEqual,
+
+    ///operator a<b
+    Less,
+
+    ///operator a<=b
+    LessEqual,
+
+    ///operator a>b
+    Greater,
+
+    ///operator a>=b
+    GreaterEqual,
+
+    /// represent all literal integer, a negative integer is a Minus followed by an Int
+    /// it can be up to 9223372036854775808, which is only valid after a Minus, for the smallest int
     Int(u64),
 
+    /// represent all literal floating number, like 1.5
+    Float(f64),
+
     /// represent all literal string (between "") and contains the string
     String(String),
 
@@ -34,43 +59,172 @@ pub enum Token{
     /// Note: currently everything with ascii alphabetic character which neither in a string or a keyword is considered as a identifier
     Identifier(String),
 
-    /// represent all keyword and store the keyword itself
-    Keyword(
-      String // FIXME maybe an enum instead of a string?
-    ),
+    /// represent all keyword
+    Keyword(Keyword),
 
     /// represent a coma
     Coma,
 
+    /// represent a colon, it separates a name from its type: `x: int`
+    Colon,
+
+    /// represent an arrow, it introduces the type returned by a function: `-> int`
+    Arrow,
+
     /// represent a opening parenthesis (
     OpeningParen,
 
     /// represent a closing parenthesis )
     ClosingParen,
 
+    /// represent a opening brace {, it begins a block
+    OpeningBrace,
+
+    /// represent a closing brace }, it ends a block
+    ClosingBrace,
+
     /// represent the assign operator, =
     Assign,
 
+    /// represent the end of a line, it separates two statements
+    NewLine,
+
+    /// represent a comment, `// ...` or `/* ... */`, and contains it with its delimiters
+    /// Only produced when the tokenizer keeps the trivia, the parser does not expect it
+    Comment(String),
+
     /// Only used when an error is encountred
     Error
 }
 
 use Token as T;
 
+
+/// the words reserved by the language, they can't be used as identifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Keyword{
+    If,
+    Else,
+    Var,
+    Print,
+    True,
+    False,
+    And,
+    Or,
+    Not,
+    While,
+    Break,
+    Continue,
+    Fn,
+    Return
+}
+
+/// every keyword, the tokenizer reads a word as a keyword if it is the name of one of them
+pub const KEYWORDS: [Keyword; 14] = [
+    Keyword::If,
+    Keyword::Else,
+    Keyword::Var,
+    Keyword::Print,
+    Keyword::True,
+    Keyword::False,
+    Keyword::And,
+    Keyword::Or,
+    Keyword::Not,
+    Keyword::While,
+    Keyword::Break,
+    Keyword::Continue,
+    Keyword::Fn,
+    Keyword::Return
+];
+
+impl Keyword{
+    /// the keyword written `name`, if there is one
+    pub fn from_name(name: &str) -> Option<Keyword>{
+        KEYWORDS.into_iter().find(|keyword| keyword.name() == name)
+    }
+
+    /// how the keyword is written
+    pub fn name(&self) -> &'static str{
+        match self {
+            Keyword::If => "if",
+            Keyword::Else => "else",
+            Keyword::Var => "var",
+            Keyword::Print => "print",
+            Keyword::True => "true",
+            Keyword::False => "false",
+            Keyword::And => "and",
+            Keyword::Or => "or",
+            Keyword::Not => "not",
+            Keyword::While => "while",
+            Keyword::Break => "break",
+            Keyword::Continue => "continue",
+            Keyword::Fn => "fn",
+            Keyword::Return => "return"
+        }
+    }
+}
+
+impl Display for Keyword{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 impl Token{
     pub fn same(&self, other: &Token) -> bool{
         std::mem::discriminant(self) == std::mem::discriminant(other)
     }
 }
 
+
+/// a position in the source code
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub struct Position{
+    pub line: u64,
+    /// the number of char before the position on its line
+    pub column: u32,
+    /// the number of bytes before the position in the source code
+    pub offset: usize
+}
+
+/// where a token is: from its first char to the char after the last one
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub struct Span{
+    pub start: Position,
+    pub end: Position
+}
+
+impl Span{
+    /// an empty span at a position
+    pub fn at(position: Position) -> Self{
+        Span { start: position, end: position }
+    }
+
+    /// the location of the beginning of the span
+    pub fn location(&self, filename: impl Into<String>) -> Location{
+        Location::new(filename, self.start.line, self.start.column)
+    }
+}
+
+/// a token and where it was found
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct SpannedToken{
+    pub token: Token,
+    pub span: Span
+}
+
+
 pub struct Tokenizer<'a>{
-    tk: Vec<Token>,
+    tk: Vec<SpannedToken>,
     err: Vec<Error>,
     iter: Peekable<Chars<'a>>,
     curr: Option<char>,
-    line: String,
+    lines: Lines<'a>,
+    line: String, // the current line, used by errors
     pl: PartialLocation,
     char_pos: i64, // here we need to use i64 instead of u32, because u32 can't handle negative number i32::MAX is smaller than u32::MAX
+    offset: usize, // the byte offset of the current char
+    keep_trivia: bool,
 }
 
 
@@ -78,39 +232,107 @@ pub struct Tokenizer<'a>{
 impl<'a> Tokenizer<'a>{
 
     /// create a new tokenizer with a string in input
-    /// This string must live longer than Tokenizer 
-    pub fn new(line: &'a str, pl: PartialLocation) -> Self{
-        let line = line.trim_end();
+    /// This string must live longer than Tokenizer
+    /// The string can have multiple lines, `pl` is the location of the first one
+    pub fn new(source: &'a str, pl: PartialLocation) -> Self{
+        let source = source.trim_end();
+        let mut lines = source.lines();
+        let line = lines.next().unwrap_or_default().into();
         Tokenizer{
             tk: Vec::new(),
             err: Vec::new(),
-            iter: line.chars().peekable(),
+            iter: source.chars().peekable(),
             curr: None,
-            line: line.into(),
+            lines,
+            line,
             pl,
-            char_pos: -1
+            char_pos: -1,
+            offset: 0,
+            keep_trivia: false
         }
         
     }
+
+    /// keep the comments as Token::Comment instead of skipping them, for tools like a formatter
+    /// can be chained
+    pub fn keep_trivia(mut self, keep: bool) -> Self{
+        self.keep_trivia = keep;
+        self
+    }
+
+    /// called when a \n is encountred
+    /// update the location, so errors on the next line are reported at the right place
+    fn new_line(&mut self){
+        self.pl.next_line();
+        self.line = self.lines.next().unwrap_or_default().into();
+        self.char_pos = -1; // the next advance is the first char of the line
+    }
     
     /// advance by one char
     /// the self.curr represent the current character
     /// however, self.curr is an `Option<char>`, so before using it we must check if it is None
     /// self.curr = None when self.char_pos > line.len()
     fn advance(&mut self){
+        if let Some(curr) = self.curr{
+            self.offset += curr.len_utf8();
+        }
         self.curr = self.iter.next();
         self.char_pos += 1
     }
 
+    /// the position of the current char
+    fn position(&self) -> Position{
+        Position { line: self.pl.get_line(), column: self.char_pos as u32, offset: self.offset }
+    }
+
+    /// push a token which begins at `start` and ends before the current char
+    fn push(&mut self, token: Token, start: Position){
+        let end = self.position();
+        self.tk.push(SpannedToken { token, span: Span { start, end } });
+    }
+
+    /// push a token made of the current char and the next one, like ==
+    /// the current char becomes the second one
+    fn push_two_chars(&mut self, token: Token){
+        let start = self.position();
+        self.advance();
+        let mut end = self.position();
+        end.column += 1;
+        end.offset += 1; // the operators are ASCII
+
+        self.tk.push(SpannedToken { token, span: Span { start, end } });
+    }
+
+    /// push a comment if the trivia are kept
+    fn push_trivia(&mut self, token: Token, start: Position){
+        if self.keep_trivia{
+            self.push(token, start);
+        }
+    }
+
+    /// push a token made of the current char only
+    fn push_char(&mut self, token: Token){
+        let start = self.position();
+        let mut end = start;
+        end.column += 1;
+        end.offset += self.curr.map(char::len_utf8).unwrap_or_default();
+
+        self.tk.push(SpannedToken { token, span: Span { start, end } });
+    }
 
-    /// try to make a Token::String
-    /// cannot fail, except if string.parse panics. 
-    fn make_int(&mut self) -> Token{
 
+    /// try to make a Token::Int or a Token::Float
+    /// if the number is too big or malformed, it returns Token::Error
+    fn make_number(&mut self) -> Token{
+        let start = self.position();
         let mut string_number = String::default();
+        let mut dots = 0;
         
         while let Some(curr) = self.curr{
-            if !curr.is_ascii_digit(){
+            if curr == '.'{
+                dots += 1;
+            }
+            else if !curr.is_ascii_digit(){
                 break;
             }
 
@@ -118,14 +340,30 @@ impl<'a> Tokenizer<'a>{
             self.advance();
         }
 
-        T::Int(string_number.parse().expect("An error occured while parsing a string to an int, in Tokenizer::make_int"))
+        if dots == 0{
+            return match string_number.parse() {
+                Ok(val) if val <= MAX_INT_MAGNITUDE => T::Int(val),
+                _ => { // the string only has digits, so the number is too big
+                    self.err.push(Error::integer_overflow(Location::from(self.pl.clone()).char_pos(start.column), self.line.clone()));
+                    T::Error
+                }
+            };
+        }
+
+        // there must be exactly one dot with digits after it, and the number must be finite
+        match string_number.parse::<f64>() {
+            Ok(val) if dots == 1 && !string_number.ends_with('.') && val.is_finite() => T::Float(val),
+            _ => {
+                self.err.push(Error::floating_numer(Location::from(self.pl.clone()).char_pos(start.column), self.line.clone()));
+                T::Error
+            }
+        }
     }
 
     /// try to make a Token::Identifier or Token::Keyword
     /// if it fails, returns Token::Error
     fn make_identifier(&mut self) -> Token{
         let mut identifier = String::default();
-        let keywords = ["if", "else", "var", "print"]; 
 
         while let Some(curr) = self.curr{
             
@@ -136,50 +374,181 @@ impl<'a> Tokenizer<'a>{
             identifier.push(self.curr.unwrap());
             self.advance();
         }
-        if keywords.contains(&identifier.as_str()){
-            T::Keyword(identifier)
-        }
-        else{
-            T::Identifier(identifier)
+        match Keyword::from_name(&identifier) {
+            Some(keyword) => T::Keyword(keyword),
+            None => T::Identifier(identifier)
         }
     }
 
     /// try to make a Token::String
+    /// the escape sequences \n, \t, \\, \", \0 and \u{...} are replaced by the char they represent
     /// if it fails, it returns Token::Error
     fn make_string(&mut self) -> Token{
+        let start = self.position();
         let mut res = String::new();
         let mut terminated = false;
+        let mut valid = true;
 
         self.advance(); // skip the "
 
         while let Some(char) = self.curr{
+            if char == '\n'{ // a string can't be on multiple lines
+                break;
+            }
+
             if char == '\"'{
                 terminated = true;
                 self.advance();
                 break;
             }
 
+            if char == '\\'{
+                match self.make_escape() {
+                    Some(char) => res.push(char),
+                    None => valid = false
+                }
+                continue;
+            }
+
             res.push(char);
             self.advance();
         }
 
-        if terminated{ 
+        if !terminated{ // the string was not closed
+            // the error points at the opening quote
+            self.err.push(Error::string_closing(Location::from(self.pl.clone()).char_pos(start.column), self.line.clone()));
+
+            T::Error // indicate the error
+        }
+        else if valid{
             T::String(res)
         }
-        else {// the string was not closed
-            self.err.push(Error::string_closing(Location::new("not specified", 0, 0), self.line.clone()));
+        else {
+            T::Error
+        }
 
-            T::Error // indicate the error
+    }
+
+    /// read an escape sequence, the current char is the backslash
+    /// returns the char it represents, or None and push an error pointing at the backslash
+    fn make_escape(&mut self) -> Option<char>{
+        let start = self.position();
+        self.advance(); // skip the \
+
+        let char = match self.curr {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('0') => '\0',
+            Some('u') => return self.make_unicode_escape(start),
+            // the end of the line is left to make_string, which reports the unclosed string
+            None | Some('\n') => return None,
+            Some(other) => {
+                self.err.push(Error::escape_sequence(Location::from(self.pl.clone()).char_pos(start.column), self.line.clone(),
+                    format!("Unknown escape sequence [\\{other}]")));
+                self.advance();
+                return None;
+            }
+        };
+
+        self.advance();
+        Some(char)
+    }
+
+    /// read the end of a \u{...} escape, the current char is the u
+    /// between the braces there must be 1 to 6 hexadecimal digits which are the code of a char
+    fn make_unicode_escape(&mut self, start: Position) -> Option<char>{
+        self.advance(); // skip the u
+
+        let mut code = None;
+        if self.curr == Some('{'){
+            let mut digits = String::new();
+            self.advance();
+
+            while let Some(curr) = self.curr{
+                if !curr.is_ascii_hexdigit(){
+                    break;
+                }
+                digits.push(curr);
+                self.advance();
+            }
+
+            if self.curr == Some('}'){
+                self.advance();
+                if (1..=6).contains(&digits.len()){
+                    code = u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32);
+                }
+            }
+        }
+
+        if code.is_none(){
+            self.err.push(Error::escape_sequence(Location::from(self.pl.clone()).char_pos(start.column), self.line.clone(),
+                "A unicode escape must be written like \\u{e9}, with the code of a valid char".into()));
+        }
+        code
+    }
+
+
+    /// make a Token::Comment from a `//` comment, which ends at the end of the line
+    /// the \n is not a part of the comment
+    fn make_line_comment(&mut self) -> Token{
+        let mut comment = String::new();
+
+        while let Some(curr) = self.curr{
+            if curr == '\n'{
+                break;
+            }
+            comment.push(curr);
+            self.advance();
+        }
+
+        T::Comment(comment)
+    }
+
+    /// make a Token::Comment from a `/* */` comment, the current char is the /
+    /// The comments can be nested: `/* a /* b */ c */` is a single comment, and they can be on multiple lines.
+    /// If it is not closed, it returns Token::Error
+    fn make_block_comment(&mut self) -> Token{
+        let start = self.position();
+        let start_line = self.line.clone();
+        let mut comment = String::new();
+        let mut depth = 0;
+
+        while let Some(curr) = self.curr{
+            let next = self.iter.peek().copied();
+
+            if (curr == '/' && next == Some('*')) || (curr == '*' && next == Some('/')){
+                depth += if curr == '/' { 1 } else { -1 };
+                comment.push(curr);
+                self.advance();
+                comment.push(self.curr.unwrap());
+                self.advance();
+
+                if depth == 0{
+                    return T::Comment(comment);
+                }
+                continue;
+            }
+
+            comment.push(curr);
+            if curr == '\n'{
+                self.new_line();
+            }
+            self.advance();
         }
 
+        // the error points at the opening /*
+        self.err.push(Error::comment_closing(Location::from(self.pl.clone()).line(start.line).char_pos(start.column), start_line));
+        T::Error
     }
 
 
     /// push an error is the current char cannot be after a litteral number or a litteral string
-    /// +, -, *, /, ), SPACE, COMA are the only character that can be directly after a number or a string
+    /// the operators, ), {, }, COMA and whitespaces are the only character that can be directly after a number or a string
     fn after_number_or_string(&mut self){
         match self.curr {
-            Some( '+' | '-' | '*' | '/' | ' ' | ',' | ')' ) => (),
+            Some( '+' | '-' | '*' | '/' | '=' | '!' | '<' | '>' | ' ' | '\t' | '\r' | '\n' | ',' | ')' | '{' | '}' ) => (),
 
             Some(_) => {
                 self.err.push(
@@ -200,60 +569,120 @@ impl<'a> Tokenizer<'a>{
         self.advance();
 
         while let Some(curr) = self.curr{
+            let start = self.position();
             
             if curr.is_ascii_digit(){
-                let tmp = self.make_int();
-                self.tk.push(tmp);
+                let tmp = self.make_number();
+                self.push(tmp, start);
                 self.after_number_or_string();
                 continue;
             }
 
             else if curr.is_ascii_alphabetic(){
                 let tmp = self.make_identifier();
-                self.tk.push(tmp);
+                self.push(tmp, start);
                 continue;
             }
 
             else if curr == '\"'{
                 let tmp = self.make_string();
-                self.tk.push(tmp);
+                self.push(tmp, start);
                 self.after_number_or_string();
                 continue;
             }
 
             else if curr == ','{
-                self.tk.push(T::Coma);
+                self.push_char(T::Coma);
+            }
+
+            else if curr == ':'{
+                self.push_char(T::Colon);
             }
 
             else if curr == '('{
-                self.tk.push(T::OpeningParen);
+                self.push_char(T::OpeningParen);
             }
 
             else if curr == ')'{
-                self.tk.push(T::ClosingParen);
+                self.push_char(T::ClosingParen);
+            }
+
+            else if curr == '{'{
+                self.push_char(T::OpeningBrace);
+            }
+
+            else if curr == '}'{
+                self.push_char(T::ClosingBrace);
             }
 
             else if curr == '+'{
-                self.tk.push(T::Plus);
+                self.push_char(T::Plus);
+            }
+
+            else if curr == '-' && self.iter.peek() == Some(&'>'){
+                self.push_two_chars(T::Arrow);
             }
 
             else if curr == '-'{
-                self.tk.push(T::Minus);
+                self.push_char(T::Minus);
             }
 
             else if curr == '*'{
-                self.tk.push(T::Mul);
+                self.push_char(T::Mul);
+            }
+
+            else if curr == '/' && self.iter.peek() == Some(&'/'){
+                let tmp = self.make_line_comment();
+                self.push_trivia(tmp, start);
+                continue;
+            }
+
+            else if curr == '/' && self.iter.peek() == Some(&'*'){
+                let tmp = self.make_block_comment();
+                self.push_trivia(tmp, start);
+                continue;
             }
 
             else if curr == '/'{
-                self.tk.push(T::Div);
+                self.push_char(T::Div);
             }
             
+            // = is the assignment, == the comparison
+            else if curr == '=' && self.iter.peek() == Some(&'='){
+                self.push_two_chars(T::Equal);
+            }
+
             else if curr == '='{
-                self.tk.push(T::Assign);
+                self.push_char(T::Assign);
+            }
+
+            // ! alone is not an operator
+            else if curr == '!' && self.iter.peek() == Some(&'='){
+                self.push_two_chars(T::NotEqual);
+            }
+
+            else if curr == '<' && self.iter.peek() == Some(&'='){
+                self.push_two_chars(T::LessEqual);
+            }
+
+            else if curr == '<'{
+                self.push_char(T::Less);
+            }
+
+            else if curr == '>' && self.iter.peek() == Some(&'='){
+                self.push_two_chars(T::GreaterEqual);
             }
 
-            else  if curr != ' '{
+            else if curr == '>'{
+                self.push_char(T::Greater);
+            }
+
+            else if curr == '\n'{
+                self.push_char(T::NewLine);
+                self.new_line();
+            }
+
+            else  if !matches!(curr, ' ' | '\t' | '\r'){
                     self.err.push(
                         Error::illegal_character(
                             Location::from(self.pl.clone()).char_pos(self.char_pos as u32),
@@ -267,7 +696,7 @@ impl<'a> Tokenizer<'a>{
     }
     
 
-    pub fn result(self) -> CompilerResult<Vec<Token>>{
+    pub fn result(self) -> CompilerResult<Vec<SpannedToken>>{
         if self.err.is_empty(){
             Ok(self.tk)
         }
@@ -292,25 +721,88 @@ mod tests {
         
         let tk = res.unwrap();
         
-        assert_eq!(tk[0], Token::Int(225));
-        assert_eq!(tk[1], Token::Keyword("var".into()));
-        assert_eq!(tk[2], Token::Identifier("valid25name".into()));
-        assert_eq!(tk[3], Token::OpeningParen);
-        assert_eq!(tk[4], Token::Int(25));
-        assert_eq!(tk[5], Token::Plus);
-        assert_eq!(tk[6], Token::Int(25));
-        assert_eq!(tk[7], Token::Minus);
-        assert_eq!(tk[8], Token::Int(6));
-        assert_eq!(tk[9], Token::Mul);
-        assert_eq!(tk[10], Token::Int(8));
-        assert_eq!(tk[11], Token::Div);
-        assert_eq!(tk[12], Token::Int(5));
-        assert_eq!(tk[13], Token::Coma);
-        assert_eq!(tk[14], Token::Int(5));
-        assert_eq!(tk[15], Token::ClosingParen);
+        assert_eq!(tk[0].token, Token::Int(225));
+        assert_eq!(tk[1].token, Token::Keyword(Keyword::Var));
+        assert_eq!(tk[2].token, Token::Identifier("valid25name".into()));
+        assert_eq!(tk[3].token, Token::OpeningParen);
+        assert_eq!(tk[4].token, Token::Int(25));
+        assert_eq!(tk[5].token, Token::Plus);
+        assert_eq!(tk[6].token, Token::Int(25));
+        assert_eq!(tk[7].token, Token::Minus);
+        assert_eq!(tk[8].token, Token::Int(6));
+        assert_eq!(tk[9].token, Token::Mul);
+        assert_eq!(tk[10].token, Token::Int(8));
+        assert_eq!(tk[11].token, Token::Div);
+        assert_eq!(tk[12].token, Token::Int(5));
+        assert_eq!(tk[13].token, Token::Coma);
+        assert_eq!(tk[14].token, Token::Int(5));
+        assert_eq!(tk[15].token, Token::ClosingParen);
 
     }
 
+    #[test]
+    fn keywords(){
+        for keyword in KEYWORDS{
+            assert_eq!(Keyword::from_name(keyword.name()), Some(keyword));
+            assert_eq!(keyword.to_string(), keyword.name());
+        }
+        assert_eq!(Keyword::from_name("continue"), Some(Keyword::Continue));
+        assert_eq!(Keyword::from_name("variable"), None);
+
+        let string = String::from("if else var print iff {1}");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(0));
+        tok.tokenize();
+        let tk = tok.result().unwrap().into_iter().map(|tk| tk.token).collect::<Vec<_>>();
+        assert_eq!(tk, vec![
+            Token::Keyword(Keyword::If), Token::Keyword(Keyword::Else), Token::Keyword(Keyword::Var),
+            Token::Keyword(Keyword::Print), Token::Identifier("iff".into()),
+            Token::OpeningBrace, Token::Int(1), Token::ClosingBrace
+        ]);
+    }
+
+    #[test]
+    fn comparisons(){
+        let string = String::from("a == b != 1<2 <= 3>=4 > x = 5==\"a\"");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(0));
+        tok.tokenize();
+        let tk = tok.result().unwrap();
+
+        assert_eq!(tk.iter().map(|tk| tk.token.clone()).collect::<Vec<_>>(), vec![
+            Token::Identifier("a".into()), Token::Equal, Token::Identifier("b".into()), Token::NotEqual,
+            Token::Int(1), Token::Less, Token::Int(2), Token::LessEqual, Token::Int(3), Token::GreaterEqual, Token::Int(4),
+            Token::Greater, Token::Identifier("x".into()), Token::Assign, Token::Int(5), Token::Equal, Token::String("a".into())
+        ]);
+        assert_eq!(tk[1].span, Span {
+            start: Position { line: 0, column: 2, offset: 2 },
+            end: Position { line: 0, column: 4, offset: 4 }
+        });
+        assert_eq!(tk[5].span.start.column, 11);
+
+        // ! alone is not an operator
+        let string = String::from("a ! b");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(0));
+        tok.tokenize();
+        assert!(tok.result().is_err());
+    }
+
+    #[test]
+    fn annotations(){
+        let string = String::from("fn f(x: int) -> string\nprint 1 - -1");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(0));
+        tok.tokenize();
+        let tk = tok.result().unwrap();
+
+        assert_eq!(tk.iter().map(|tk| tk.token.clone()).collect::<Vec<_>>(), vec![
+            Token::Keyword(Keyword::Fn), Token::Identifier("f".into()), Token::OpeningParen, Token::Identifier("x".into()), Token::Colon,
+            Token::Identifier("int".into()), Token::ClosingParen, Token::Arrow, Token::Identifier("string".into()), Token::NewLine,
+            Token::Keyword(Keyword::Print), Token::Int(1), Token::Minus, Token::Minus, Token::Int(1)
+        ]);
+        assert_eq!(tk[7].span, Span {
+            start: Position { line: 0, column: 13, offset: 13 },
+            end: Position { line: 0, column: 15, offset: 15 }
+        });
+    }
+
     #[test]
     fn check_after_number(){
         let string = String::from("1024better");
@@ -361,9 +853,72 @@ mod tests {
         assert!(res.is_ok());
         let unwrapped = res.unwrap();
         
-        assert_eq!(unwrapped[0], Token::String("hello world".into()));
-        assert_eq!(unwrapped[1], Token::String("125".into()));
+        assert_eq!(unwrapped[0].token, Token::String("hello world".into()));
+        assert_eq!(unwrapped[1].token, Token::String("125".into()));
+
+    }
+
+    #[test]
+    fn escapes(){
+        let string = String::from(r#""a\"b\\c\n\t\0" "\u{e9}\u{1F600}""#);
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(0));
+        tok.tokenize();
+        let tk = tok.result().unwrap();
 
+        assert_eq!(tk[0].token, Token::String("a\"b\\c\n\t\0".into()));
+        assert_eq!(tk[1].token, Token::String("é😀".into()));
+
+        // the error points at the backslash, and the other escapes are checked too
+        let string = String::from(r#"print "ab\q" + "\u{110000}" + "\u{zz}" + "\u41""#);
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(0));
+        tok.tokenize();
+        let err = tok.result().unwrap_err();
+
+        assert_eq!(err.len(), 4);
+        assert!(err[0].to_string().contains("EscapeSequence in test at 0:9"));
+        assert!(err[0].to_string().contains("[\\q]"));
+        assert!(err[1].to_string().contains("in test at 0:16"));
+
+        // an escaped quote does not close the string
+        let string = String::from(r#""unclosed\""#);
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(0));
+        tok.tokenize();
+        let err = tok.result().unwrap_err();
+        assert_eq!(err.len(), 1);
+        assert!(err[0].to_string().contains("StringClosingError"));
+    }
+
+    #[test]
+    fn comments(){
+        let string = String::from("var x = 1 // x is /* one */\nvar /* a /* nested */ comment\n on two lines */ y = 2/3 /**/");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(1));
+        tok.tokenize();
+        let tk = tok.result().unwrap();
+
+        assert_eq!(tk.iter().map(|tk| tk.token.clone()).collect::<Vec<_>>(), vec![
+            Token::Keyword(Keyword::Var), Token::Identifier("x".into()), Token::Assign, Token::Int(1), Token::NewLine,
+            Token::Keyword(Keyword::Var), Token::Identifier("y".into()), Token::Assign, Token::Int(2), Token::Div, Token::Int(3)
+        ]);
+        // the lines are still counted inside a comment
+        assert_eq!(tk[6].span.start, Position { line: 3, column: 17, offset: 75 });
+
+        // the comments are kept as trivia
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(1)).keep_trivia(true);
+        tok.tokenize();
+        let tk = tok.result().unwrap();
+
+        assert_eq!(tk[4].token, Token::Comment("// x is /* one */".into()));
+        assert_eq!(tk[7].token, Token::Comment("/* a /* nested */ comment\n on two lines */".into()));
+        assert_eq!(&string[tk[7].span.start.offset..tk[7].span.end.offset], "/* a /* nested */ comment\n on two lines */");
+        assert_eq!(tk[13].token, Token::Comment("/**/".into()));
+
+        // the unclosed comment error points at the opening /*
+        let string = String::from("var x = 1\n/* a /* b */\nprint x");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(1));
+        tok.tokenize();
+        let err = tok.result().unwrap_err();
+        assert_eq!(err.len(), 1);
+        assert!(err[0].to_string().contains("CommentClosingError in test at 2:0\n|\t/* a /* b */"));
     }
 
     #[test]
@@ -377,4 +932,118 @@ mod tests {
         let err = res.unwrap_err();
         assert_eq!(err.len(), 1);
     }
+
+    #[test]
+    fn multiple_lines(){
+        let string = String::from("var x = 25\r\n\tprint x\n\nprint \"a");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(1));
+        tok.tokenize();
+        let res = tok.result();
+
+        // the unclosed string is on the fourth line
+        let err = res.unwrap_err();
+        assert_eq!(err.len(), 1);
+        assert!(err[0].to_string().contains("print \"a"));
+
+        let string = String::from("var x = 25\nprint x\n");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(1));
+        tok.tokenize();
+        let tk = tok.result().unwrap().into_iter().map(|tk| tk.token).collect::<Vec<_>>();
+
+        assert_eq!(tk, vec![
+            Token::Keyword(Keyword::Var), Token::Identifier("x".into()), Token::Assign, Token::Int(25), Token::NewLine,
+            Token::Keyword(Keyword::Print), Token::Identifier("x".into())
+        ]);
+
+        let string = String::from("var x = 25\nvar y = é");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(1));
+        tok.tokenize();
+        let err = tok.result().unwrap_err();
+        assert!(err[0].to_string().contains("in test at 2:8"));
+    }
+
+    #[test]
+    fn spans(){
+        let string = String::from("var é = 25\n  \"hi\" + x");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(1));
+        tok.tokenize();
+
+        // é is illegal, but the tokens around it are still located
+        assert_eq!(tok.tk[0].span, Span {
+            start: Position { line: 1, column: 0, offset: 0 },
+            end: Position { line: 1, column: 3, offset: 3 }
+        });
+        // é is 2 bytes long but only one char
+        assert_eq!(tok.tk[1].span, Span {
+            start: Position { line: 1, column: 6, offset: 7 },
+            end: Position { line: 1, column: 7, offset: 8 }
+        });
+        assert_eq!(tok.tk[2].span.start, Position { line: 1, column: 8, offset: 9 });
+        assert_eq!(tok.tk[2].span.end, Position { line: 1, column: 10, offset: 11 });
+        assert_eq!(tok.tk[3].token, Token::NewLine);
+        assert_eq!(tok.tk[4].span, Span {
+            start: Position { line: 2, column: 2, offset: 14 },
+            end: Position { line: 2, column: 6, offset: 18 }
+        });
+        assert_eq!(&string[tok.tk[4].span.start.offset..tok.tk[4].span.end.offset], "\"hi\"");
+        assert_eq!(tok.tk[6].span.start, Position { line: 2, column: 9, offset: 21 });
+
+        // the unclosed string error points at the opening quote
+        let string = String::from("var x = \"unclosed");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(1));
+        tok.tokenize();
+        let err = tok.result().unwrap_err();
+        assert!(err[0].to_string().contains("in test at 1:8"));
+    }
+
+    #[test]
+    fn check_integer_overflow(){
+        let string = String::from("9223372036854775807 + 1");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(0));
+        tok.tokenize();
+        assert!(tok.result().is_ok());
+
+        // the magnitude of the smallest int is checked by the parser, it needs a minus before it
+        let string = String::from("9223372036854775808");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(0));
+        tok.tokenize();
+        assert_eq!(tok.result().unwrap()[0].token, Token::Int(9223372036854775808));
+
+        let string = String::from("9223372036854775809");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(0));
+        tok.tokenize();
+        assert!(tok.result().is_err());
+
+        // the tokenizer continues after the literal, so the other errors of the line are reported
+        let string = String::from("var x = 99999999999999999999999 + é");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(0));
+        tok.tokenize();
+        let err = tok.result().unwrap_err();
+
+        assert_eq!(err.len(), 2);
+        assert!(err[0].to_string().contains("IntegerOverflow in test at 0:8"));
+    }
+
+    #[test]
+    fn check_float(){
+        let string = String::from("1.5 12.50 0.25+3");
+        let mut tok = Tokenizer::new(&string, PartialLocation::testing(0));
+        tok.tokenize();
+        let tk = tok.result().unwrap();
+
+        assert_eq!(tk[0].token, Token::Float(1.5));
+        assert_eq!(tk[1].token, Token::Float(12.5));
+        assert_eq!(tk[2].token, Token::Float(0.25));
+        assert_eq!(tk[2].span.end.column, 14);
+        assert_eq!(tk[4].token, Token::Int(3));
+
+        // malformed floating numbers
+        for string in ["1.2.3", "1.", "5..2"]{
+            let mut tok = Tokenizer::new(string, PartialLocation::testing(0));
+            tok.tokenize();
+            let err = tok.result().unwrap_err();
+            assert_eq!(err.len(), 1, "{string}");
+            assert!(err[0].to_string().contains("FloatingNumber in test at 0:0"));
+        }
+    }
 }
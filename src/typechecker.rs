@@ -0,0 +1,642 @@
+#![allow(dead_code)]
+
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{ast::{Expr, Operator, Param, Statement, Type, UnaryOperator}, errors::{CompilerResult, Error, Location}, resolver::{Resolutions, Symbol}};
+
+
+/// the type of the result of a binary operation, or the description of the problem
+/// - `and` and `or` take two bools
+/// - the comparisons take two numbers or two strings, `==` and `!=` also take two bools
+/// - the arithmetic gives an int between two int, a float between an int and a float or two floats
+/// - `+` concatenates two strings, the other values must be converted explicitly
+pub fn binary_type(opr: &Operator, l: Type, r: Type) -> Result<Type, String>{
+    match (opr, l, r) {
+        (Operator::And | Operator::Or, Type::Bool, Type::Bool) => Ok(Type::Bool),
+        (opr, Type::Int | Type::Float, Type::Int | Type::Float) if opr.is_comparison() => Ok(Type::Bool),
+        (opr, Type::String, Type::String) if opr.is_comparison() => Ok(Type::Bool),
+        (Operator::Equal | Operator::NotEqual, Type::Bool, Type::Bool) => Ok(Type::Bool),
+
+        (opr, Type::Int, Type::Int) if !opr.is_comparison() && !opr.is_logical() => Ok(Type::Int),
+        (opr, Type::Int | Type::Float, Type::Int | Type::Float) if !opr.is_comparison() && !opr.is_logical() => Ok(Type::Float),
+        (Operator::Plus, Type::String, Type::String) => Ok(Type::String),
+
+        _ => Err(format!("The operator [{opr}] can't be used between {l} and {r}"))
+    }
+}
+
+/// the type of the result of a unary operation, or the description of the problem
+pub fn unary_type(opr: &UnaryOperator, ty: Type) -> Result<Type, String>{
+    match (opr, ty) {
+        (UnaryOperator::Neg, Type::Int | Type::Float) => Ok(ty),
+        (UnaryOperator::Neg, _) => Err(format!("A {ty} can't be negated")),
+        (UnaryOperator::Not, Type::Bool) => Ok(Type::Bool),
+        (UnaryOperator::Not, _) => Err(format!("The operand of [not] must be a bool, not a {ty}"))
+    }
+}
+
+
+/// what a function returns, it is known at its first return or from its annotation
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Returns{
+    Unknown,
+    Nothing,
+    Value(Type)
+}
+
+impl Display for Returns{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Returns::Unknown => write!(f, "unknown"),
+            Returns::Nothing => write!(f, "nothing"),
+            Returns::Value(ty) => write!(f, "a {ty}"),
+        }
+    }
+}
+
+/// a function checked for some types of arguments
+#[derive(Clone)]
+struct Instance{
+    args: Vec<Type>,
+    returns: Returns,
+    checking: bool, // its body is being checked
+    recursive: bool, // its value was used by a call in its body, before its type was known
+    unknown_return: bool // it returned a value whose type was not known
+}
+
+/// a function of the language
+#[derive(Clone)]
+struct Function{
+    params: Vec<Param>,
+    returns: Option<Type>, // the annotation of the type returned
+    body: Expr,
+    instances: Vec<Instance>
+}
+
+
+/// the function and the types of arguments a body is checked for, None outside of the functions
+/// the same expression can have a different type in each instance of a function
+pub type Context = Option<(String, Vec<Type>)>;
+
+/// The types found by the TypeChecker, so the C backend does not have to find them again.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Types{
+    returns: HashMap<(String, Vec<Type>), Option<Type>>, // what each function returns for each list of argument types, None if it returns nothing
+    exprs: HashMap<(Context, Location), Type>, // the type of each variable, call and operation, found by its location
+    variables: HashMap<(Context, Symbol), Type> // the type of each variable declared
+}
+
+impl Types{
+    /// what a function returns when it is called with these types of arguments, None if it returns nothing
+    /// panics if the function was never checked with these types
+    pub fn returns(&self, identifier: &str, args: &[Type]) -> Option<Type>{
+        self.returns[&(identifier.to_string(), args.to_vec())]
+    }
+
+    /// the type of an expression, a litteral has its own type
+    /// panics if the expression was not checked in this context
+    pub fn expr(&self, context: &Context, expr: &Expr) -> Type{
+        match expr {
+            Expr::IntLitteral(_) => Type::Int,
+            Expr::FloatLitteral(_) => Type::Float,
+            Expr::StringLitteral(_) => Type::String,
+            Expr::BoolLitteral(_) => Type::Bool,
+            _ => {
+                let location = expr.location().expect("a block or an error has no type");
+                self.exprs[&(context.clone(), location.clone())]
+            }
+        }
+    }
+
+    /// the type of a variable
+    /// panics if its declaration was not checked in this context
+    pub fn variable(&self, context: &Context, symbol: Symbol) -> Type{
+        self.variables[&(context.clone(), symbol)]
+    }
+}
+
+
+/// Find the type of every expression before the program is executed or translated.
+/// A variable has the type of the value it is declared with, until it is declared again.
+/// A function is checked each time it is called with new types of arguments, like it was a new function,
+/// and every return of a function must give the same type.
+/// The annotations are checked against the types found, a function whose parameters are all annotated is checked even if it is not called.
+///
+/// The variables are found with the symbols of the Resolver, the names which are not declared are reported by the Resolver and ignored here.
+/// The TypeChecker keeps its variables, so the REPL can check each line with the variables of the previous ones.
+#[derive(Clone)]
+pub struct TypeChecker{
+    variables: HashMap<Symbol, Option<Type>>, // the type of the variables of the function which is checked, None if it is unknown
+    resolutions: Resolutions, // the symbol of each name of the statements
+    functions: HashMap<String, Function>,
+    function: Option<(String, usize)>, // the function and the instance which are checked, None outside of the functions
+    context: Context, // the function and the types of arguments which are checked
+    types: Types, // the types found for the expressions and the variables, the returns are added by types
+    err: Vec<Error>
+}
+
+impl TypeChecker{
+    pub fn new() -> Self{
+        TypeChecker {
+            variables: HashMap::new(),
+            resolutions: Resolutions::default(),
+            functions: HashMap::new(),
+            function: None,
+            context: None,
+            types: Types::default(),
+            err: Vec::new()
+        }
+    }
+
+    /// the type of the variable used or declared at `location`
+    fn variable(&self, location: &Location) -> Option<Type>{
+        let symbol = self.resolutions.symbol(location)?;
+        self.variables.get(&symbol).copied().flatten()
+    }
+
+    /// give its type to the variable declared at `location`
+    fn declare(&mut self, location: &Location, ty: Option<Type>){
+        if let Some(symbol) = self.resolutions.symbol(location){
+            self.variables.insert(symbol, ty);
+            if let Some(ty) = ty{
+                self.types.variables.insert((self.context.clone(), symbol), ty);
+            }
+        }
+    }
+
+    /// find the type of an expression and record it
+    /// returns None if it is unknown, after an error or in a recursive function before its type is known
+    fn expr(&mut self, expr: &Expr, location: &Location) -> Option<Type>{
+        let ty = self.expr_type(expr, location);
+        if let (Some(ty), Some(expr_location)) = (ty, expr.location()){
+            self.types.exprs.insert((self.context.clone(), expr_location.clone()), ty);
+        }
+        ty
+    }
+
+    /// find the type of an expression
+    /// `location` is the location of the statement, used by errors
+    fn expr_type(&mut self, expr: &Expr, location: &Location) -> Option<Type>{
+        match expr {
+            Expr::IntLitteral(_) => Some(Type::Int),
+            Expr::FloatLitteral(_) => Some(Type::Float),
+            Expr::StringLitteral(_) => Some(Type::String),
+            Expr::BoolLitteral(_) => Some(Type::Bool),
+
+            Expr::Identifier { location, .. } => self.variable(location),
+
+            Expr::Call { identifier, args, location } => {
+                let index = self.call(identifier, args, location)?;
+                let instance = &mut self.functions.get_mut(identifier).unwrap().instances[index];
+
+                match instance.returns {
+                    Returns::Value(ty) => Some(ty),
+                    Returns::Nothing => {
+                        self.err.push(Error::no_return_value(location.clone(), String::new(), identifier.clone()));
+                        None
+                    },
+                    // a recursive call, its body is checked again when its type is known
+                    Returns::Unknown => {
+                        instance.recursive = true;
+                        None
+                    }
+                }
+            },
+
+            Expr::BinaryExpr { opr, l, r, .. } => {
+                let l = self.expr(l, location);
+                let r = self.expr(r, location);
+
+                binary_type(opr, l?, r?).map_err(|desc| {
+                    self.err.push(Error::type_mismatch(location.clone(), String::new(), desc));
+                }).ok()
+            },
+
+            Expr::UnaryExpr { opr, value, .. } => {
+                let ty = self.expr(value, location)?;
+
+                unary_type(opr, ty).map_err(|desc| {
+                    self.err.push(Error::type_mismatch(location.clone(), String::new(), desc));
+                }).ok()
+            },
+
+            Expr::Block { .. } | Expr::Error => None
+        }
+    }
+
+    /// check that a condition is a bool
+    fn condition(&mut self, condition: &Expr, location: &Location){
+        match self.expr(condition, location) {
+            Some(Type::Bool) | None => (),
+            Some(ty) => {
+                self.err.push(Error::type_mismatch(location.clone(), String::new(), format!("A condition must be a bool, not a {ty}")));
+            }
+        }
+    }
+
+    /// check a call, returns the index of the instance which is called
+    fn call(&mut self, identifier: &str, args: &[Expr], location: &Location) -> Option<usize>{
+        // every argument is checked, even if the function does not exist
+        let args = args.iter().map(|arg| self.expr(arg, location)).collect::<Vec<_>>();
+        let function = self.functions.get(identifier)?;
+        if function.params.len() != args.len(){
+            return None; // reported by the Resolver
+        }
+
+        // an annotated parameter gives its type to the argument, even if the type of the argument is unknown
+        let mut types = Vec::new();
+        for (param, arg) in function.params.iter().zip(args){
+            match (param.ty, arg) {
+                (Some(ty), Some(arg)) if ty != arg => {
+                    self.err.push(Error::type_mismatch(
+                        location.clone(), String::new(),
+                        format!("The parameter [{}] of [{identifier}] is a {ty}, it can't be a {arg}", param.identifier)
+                    ));
+                    return None;
+                },
+                (ty, arg) => types.push(ty.or(arg))
+            }
+        }
+        let types = types.into_iter().collect::<Option<Vec<_>>>()?;
+
+        let index = self.instance(identifier, types);
+        if self.functions[identifier].instances[index].returns == Returns::Unknown && !self.functions[identifier].instances[index].checking{
+            self.err.push(Error::type_mismatch(
+                location.clone(), String::new(),
+                format!("The type of [{identifier}] is unknown, it must return a value which does not depend on its own value")
+            ));
+            return None;
+        }
+
+        Some(index)
+    }
+
+    /// the index of the instance of a function for some types of arguments
+    /// the function is checked if it was never called with these types
+    fn instance(&mut self, identifier: &str, args: Vec<Type>) -> usize{
+        let function = self.functions.get_mut(identifier).unwrap(); // the caller checked it
+        let (index, new) = match function.instances.iter().position(|instance| instance.args == args) {
+            Some(index) => (index, false),
+            None => {
+                let returns = function.returns.map(Returns::Value).unwrap_or(Returns::Unknown);
+                function.instances.push(Instance { args, returns, checking: false, recursive: false, unknown_return: false });
+                (function.instances.len() - 1, true)
+            }
+        };
+
+        // an instance is checked again if its type was not found, the calls in it may be known now
+        let instance = &function.instances[index];
+        if new || (instance.returns == Returns::Unknown && !instance.checking){
+            self.check_instance(identifier, index);
+        }
+
+        index
+    }
+
+    /// check the body of a function for the types of arguments of an instance
+    fn check_instance(&mut self, identifier: &str, index: usize){
+        let function = self.functions.get_mut(identifier).unwrap();
+        let (params, body) = (function.params.clone(), function.body.clone());
+        let instance = &mut function.instances[index];
+        instance.checking = true;
+        instance.recursive = false;
+        instance.unknown_return = false;
+        let args = instance.args.clone();
+
+        let variables = std::mem::take(&mut self.variables);
+        let caller = self.function.replace((identifier.into(), index));
+        let caller_context = self.context.replace((identifier.into(), args.clone()));
+        for (param, ty) in params.iter().zip(args){
+            self.declare(&param.location, Some(ty));
+        }
+
+        // the functions called by the body may depend on its type, they are checked again with the body
+        let (functions, errors) = (self.functions.clone(), self.err.len());
+        self.block(&body);
+        let instance = &self.functions[identifier].instances[index];
+        if instance.recursive && instance.returns != Returns::Unknown{
+            // the type of the recursive calls is known now, the errors of the first check may come from the unknown type
+            let returns = instance.returns;
+            self.functions = functions;
+            self.functions.get_mut(identifier).unwrap().instances[index].returns = returns;
+            self.err.truncate(errors);
+            self.block(&body);
+        }
+
+        let instance = &mut self.functions.get_mut(identifier).unwrap().instances[index];
+        if instance.returns == Returns::Unknown && !instance.unknown_return{
+            instance.returns = Returns::Nothing;
+        }
+        instance.checking = false;
+
+        self.variables = variables;
+        self.function = caller;
+        self.context = caller_context;
+    }
+
+    /// check that every return of a function gives the same type
+    fn return_statement(&mut self, value: Option<&Expr>, location: &Location){
+        let returns = match value {
+            Some(value) => self.expr(value, location).map(Returns::Value),
+            None => Some(Returns::Nothing)
+        };
+
+        let Some((identifier, index)) = self.function.clone() else {
+            return; // the parser only allows return in a function
+        };
+        let instance = &mut self.functions.get_mut(&identifier).unwrap().instances[index];
+
+        match returns {
+            Some(returns) if instance.returns == Returns::Unknown => instance.returns = returns,
+            Some(returns) if instance.returns != returns => {
+                let previous = instance.returns;
+                self.err.push(Error::type_mismatch(
+                    location.clone(), String::new(),
+                    format!("The function [{identifier}] returns {previous}, it can't return {returns}")
+                ));
+            },
+            Some(_) => (),
+            None => instance.unknown_return = true
+        }
+    }
+
+    /// check the statements of a Expr::Block
+    fn block(&mut self, block: &Expr){
+        if let Expr::Block { code } = block{
+            for statement in code{
+                self.statement(statement);
+            }
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement){
+        match statement {
+            Statement::VarDeclaration { identifier, ty, value, location } => {
+                let value = self.expr(value, location);
+
+                if let (Some(ty), Some(value)) = (ty, value){
+                    if *ty != value{
+                        self.err.push(Error::type_mismatch(
+                            location.clone(), String::new(),
+                            format!("The variable [{identifier}] is a {ty}, it can't be set to a {value}")
+                        ));
+                    }
+                }
+                // the annotation is the type of the variable, even if the value is wrong
+                self.declare(location, ty.or(value));
+            },
+
+            Statement::VarEdit { identifier, value, location } => {
+                let value = self.expr(value, location);
+
+                if let (Some(ty), Some(value)) = (self.variable(location), value){
+                    if ty != value{
+                        self.err.push(Error::type_mismatch(
+                            location.clone(), String::new(),
+                            format!("The variable [{identifier}] is a {ty}, it can't be set to a {value}")
+                        ));
+                    }
+                }
+            },
+
+            Statement::Print { value, location } | Statement::Expr { value, location } => {
+                self.expr(value, location);
+            },
+
+            Statement::FuncCall { identifier, args, location } => {
+                self.call(identifier, args, location);
+            },
+
+            // the functions are declared by check, their body is checked when they are called
+            Statement::FuncDeclaration { .. } => (),
+
+            Statement::Return { value, location } => self.return_statement(value.as_ref(), location),
+
+            Statement::If { condition, body, else_body, location } => {
+                self.condition(condition, location);
+                self.block(body);
+                if let Some(else_body) = else_body{
+                    self.block(else_body);
+                }
+            },
+
+            Statement::While { condition, body, location } => {
+                self.condition(condition, location);
+                self.block(body);
+            },
+
+            Statement::Break { .. } | Statement::Continue { .. } | Statement::NoneOrError => ()
+        }
+    }
+
+    /// the types found for the expressions, the variables and the functions
+    fn types(&self) -> Types{
+        let mut types = self.types.clone();
+
+        for (identifier, function) in &self.functions{
+            for instance in &function.instances{
+                let returns = match instance.returns {
+                    Returns::Value(ty) => Some(ty),
+                    Returns::Nothing => None,
+                    Returns::Unknown => continue
+                };
+                types.returns.insert((identifier.clone(), instance.args.clone()), returns);
+            }
+        }
+
+        types
+    }
+
+    /// check the statements, the functions are declared first
+    /// `resolutions` are the symbols found by the Resolver for these statements and the previous ones
+    /// the errors don't have their line of code, the caller knows the source
+    pub fn check(&mut self, statements: &[Statement], resolutions: &Resolutions) -> CompilerResult<Types>{
+        self.resolutions = resolutions.clone();
+        for statement in statements{
+            if let Statement::FuncDeclaration { identifier, params, returns, body, .. } = statement{
+                self.functions.insert(identifier.clone(), Function { params: params.clone(), returns: *returns, body: body.clone(), instances: Vec::new() });
+            }
+        }
+
+        // the types of the arguments of these functions are known before they are called
+        for statement in statements{
+            if let Statement::FuncDeclaration { identifier, params, .. } = statement{
+                if let Some(args) = params.iter().map(|param| param.ty).collect::<Option<Vec<_>>>(){
+                    self.instance(identifier, args);
+                }
+            }
+        }
+
+        for statement in statements{
+            self.statement(statement);
+        }
+
+        if self.err.is_empty(){
+            Ok(self.types())
+        }
+        else {
+            // a function can be checked several times
+            let mut errs = std::mem::take(&mut self.err);
+            errs.sort();
+            errs.dedup();
+            Err(errs)
+        }
+    }
+}
+
+impl Default for TypeChecker{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{program::Program, resolver::Resolver};
+
+    /// parse, resolve then check the types of a program
+    fn check(source: &str) -> CompilerResult<Types>{
+        let statements = Program::new("test", source).parse()?;
+        let mut resolver = Resolver::new();
+        resolver.resolve(&statements)?;
+        TypeChecker::new().check(&statements, resolver.resolutions())
+    }
+
+    #[test]
+    fn rules(){
+        assert_eq!(binary_type(&Operator::Plus, Type::Int, Type::Int), Ok(Type::Int));
+        assert_eq!(binary_type(&Operator::Div, Type::Int, Type::Float), Ok(Type::Float));
+        assert_eq!(binary_type(&Operator::Plus, Type::String, Type::String), Ok(Type::String));
+        assert_eq!(binary_type(&Operator::Less, Type::String, Type::String), Ok(Type::Bool));
+        assert_eq!(binary_type(&Operator::Equal, Type::Bool, Type::Bool), Ok(Type::Bool));
+
+        assert!(binary_type(&Operator::Minus, Type::String, Type::Int).is_err());
+        assert!(binary_type(&Operator::Plus, Type::String, Type::Int).is_err());
+        assert!(binary_type(&Operator::Plus, Type::Bool, Type::String).is_err());
+        assert!(binary_type(&Operator::Mul, Type::String, Type::String).is_err());
+        assert!(binary_type(&Operator::Plus, Type::Bool, Type::Int).is_err());
+        assert!(binary_type(&Operator::And, Type::Int, Type::Int).is_err());
+        assert!(binary_type(&Operator::Less, Type::Bool, Type::Bool).is_err());
+        assert!(binary_type(&Operator::Equal, Type::String, Type::Int).is_err());
+
+        assert_eq!(unary_type(&UnaryOperator::Neg, Type::Float), Ok(Type::Float));
+        assert!(unary_type(&UnaryOperator::Neg, Type::String).is_err());
+        assert!(unary_type(&UnaryOperator::Not, Type::Int).is_err());
+    }
+
+    #[test]
+    fn expressions(){
+        assert!(check(include_str!("../grammar.txt")).is_ok());
+        assert!(check("var s = \"hello world\" + \"no\" + \"25\"\nprint s + s").is_ok());
+
+        // a string is only concatenated with a string
+        let errs = check("var s = \"hello world\" + \"no\" + 25").unwrap_err();
+        assert!(errs[0].to_string().contains("The operator [+] can't be used between string and int"));
+
+        let errs = check("var s = \"a\"\nprint s - 1").unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("TypeMismatch in test at 2:0"));
+        assert!(errs[0].to_string().contains("The operator [-] can't be used between string and int"));
+        assert!(check("print \"a\" * \"b\"").is_err());
+
+        // the right operand of and/or is checked even if it is not computed
+        assert!(check("print true or 1").is_err());
+        assert!(check("var x = 1\nx = 1.5").is_err());
+        assert!(check("if \"a\" { }").is_err());
+        assert!(check("while 1 + 1 { }").is_err());
+
+        // an expression with an error does not give other errors
+        assert_eq!(check("var x = -\"a\"\nprint x * 2\nx = 1").unwrap_err().len(), 1);
+        // a variable declared again can change its type
+        assert!(check("var x = 1\nvar x = \"a\"\nx = \"b\"").is_ok());
+    }
+
+    #[test]
+    fn functions(){
+        let types = check("fn id(x) { return x }\nprint id(1)\nprint id(\"a\")\nfn hi() { print 1 }\nhi()").unwrap();
+        assert_eq!(types.returns("id", &[Type::Int]), Some(Type::Int));
+        assert_eq!(types.returns("id", &[Type::String]), Some(Type::String));
+        assert_eq!(types.returns("hi", &[]), None);
+
+        // the recursive calls are checked when the type is known, even if they come first
+        let types = check("fn fact(n) {\n    if n > 1 { return n * fact(n - 1) }\n    return 1\n}\nprint fact(5)").unwrap();
+        assert_eq!(types.returns("fact", &[Type::Int]), Some(Type::Int));
+        let source = "fn even(n) {\n    if n == 0 { return true }\n    return odd(n - 1)\n}\nfn odd(n) {\n    if n == 0 { return false }\n    return even(n - 1)\n}\nprint even(4)";
+        assert_eq!(check(source).unwrap().returns("odd", &[Type::Int]), Some(Type::Bool));
+        // the order of the statements does not matter
+        let source = "fn even(n) {\n    if n != 0 { return odd(n - 1) }\n    return true\n}\nfn odd(n) {\n    return not even(n)\n}\nprint even(4)";
+        assert_eq!(check(source).unwrap().returns("odd", &[Type::Int]), Some(Type::Bool));
+        let source = "fn a(n) {\n    if n > 0 { return b(n - 1) }\n    return 0\n}\nfn b(n) { return a(n) + 1 }\nprint a(3)";
+        assert_eq!(check(source).unwrap().returns("b", &[Type::Int]), Some(Type::Int));
+
+        let errs = check("fn f(n) { return f(n) }\nf(1)").unwrap_err();
+        assert!(errs[0].to_string().contains("The type of [f] is unknown"));
+
+        // an error in a function is reported once
+        let errs = check("fn f(n) {\n    if n > 1 { return f(n - 1) }\n    print \"a\" - n\n    return 1\n}\nprint f(2)").unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("in test at 3:4"));
+
+        assert!(check("fn f(n) {\n    if n { return 1 }\n    return \"a\"\n}\nprint f(true)").is_err());
+        assert!(check("fn f() { }\nprint f()").is_err());
+        assert!(check("fn f(x) { return x - 1 }\nprint f(1)\nprint f(\"a\")").is_err());
+        // a function is only checked when it is called
+        assert!(check("fn f(x) { return x - 1 }").is_ok());
+    }
+
+    #[test]
+    fn recorded_types(){
+        let source = "var x = 1\nvar s = \"a\" + \"x\"\nfn id(v) {\n    return v\n}\nprint id(x) * id(1.5)";
+        let statements = Program::new("test", source).parse().unwrap();
+        let mut resolver = Resolver::new();
+        resolver.resolve(&statements).unwrap();
+        let types = TypeChecker::new().check(&statements, resolver.resolutions()).unwrap();
+
+        // the types of the variables and of the expressions are found by their location
+        let symbol = |line, char_pos| resolver.resolutions().symbol(&Location::new("test", line, char_pos)).unwrap();
+        assert_eq!(types.variable(&None, symbol(1, 0)), Type::Int);
+        assert_eq!(types.variable(&None, symbol(2, 0)), Type::String);
+        let (Statement::VarDeclaration { value, .. }, Statement::Print { value: print, .. }) = (&statements[1], &statements[3]) else {
+            panic!("excepted a var declaration and a print");
+        };
+        assert_eq!(types.expr(&None, value), Type::String);
+        assert_eq!(types.expr(&None, print), Type::Float);
+
+        // each instance of a function has its own types
+        let Statement::FuncDeclaration { body: Expr::Block { code }, .. } = &statements[2] else {
+            panic!("excepted a function");
+        };
+        let Statement::Return { value: Some(value), .. } = &code[0] else {
+            panic!("excepted a return");
+        };
+        assert_eq!(types.expr(&Some(("id".into(), vec![Type::Int])), value), Type::Int);
+        assert_eq!(types.expr(&Some(("id".into(), vec![Type::Float])), value), Type::Float);
+        assert_eq!(types.variable(&Some(("id".into(), vec![Type::Float])), symbol(3, 6)), Type::Float);
+    }
+
+    #[test]
+    fn annotations(){
+        assert!(check("var x: int = 1 + 2\nvar s: string = \"a\" + \"b\"").is_ok());
+        let errs = check("var x: float = 1\nx = 2").unwrap_err();
+        assert_eq!(errs.len(), 2);
+        assert!(errs[0].to_string().contains("The variable [x] is a float, it can't be set to a int"));
+
+        let errs = check("fn f(s: string, n) { print s + n }\nf(1, \"b\")\nf(\"a\", \"b\")").unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("The parameter [s] of [f] is a string, it can't be a int"));
+
+        // a function whose parameters are all annotated is checked without calls
+        let errs = check("fn f(n: int) -> string {\n    if n > 0 { return n }\n    return \"a\"\n}").unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].to_string().contains("TypeMismatch in test at 2:15"));
+        assert!(check("fn f(n: int) { print n - \"a\" }").is_err());
+        assert!(check("fn f(n: int) -> int { return }").is_err());
+
+        // the annotation of the type returned is known by the recursive calls
+        let types = check("fn f(n: int) -> int {\n    return f(n - 1) + 1\n}").unwrap();
+        assert_eq!(types.returns("f", &[Type::Int]), Some(Type::Int));
+        let types = check("fn g(x) -> bool { return g(x) }\nprint g(1.5)").unwrap();
+        assert_eq!(types.returns("g", &[Type::Float]), Some(Type::Bool));
+    }
+}